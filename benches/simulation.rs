@@ -0,0 +1,132 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use ants::config::Config;
+use ants::coordinate::Coordinate;
+use ants::food::FoodKind;
+use ants::history::History;
+use ants::navigable::Navigable;
+use ants::scent::Scent;
+use ants::world::{self, World};
+
+const SEED: u64 = 42;
+/// Ticks to run a fixture for before measuring, so that trails have had time to form
+const WARMUP_TICKS: u32 = 300;
+const COLONY_SIZES: [u32; 3] = [10, 100, 500];
+
+/// A seeded world with a fixed number of ants which neither die nor breed
+fn colony(ants: u32) -> World {
+    let mut world = World::builder()
+        .seed(SEED)
+        .config(Config {
+            starting_sugar: 0,
+            starting_ants: 0,
+            starting_protein: 0,
+            egg_sugar: u32::MAX,
+            larva_protein: u32::MAX,
+            ant_life: u32::MAX,
+            ..Config::default()
+        })
+        .build();
+    for _ in 0..ants {
+        world.spawn_ant(0);
+    }
+    for _ in 0..WARMUP_TICKS {
+        world.step();
+    }
+    world
+}
+
+/// Where an ant would be standing in the middle of things
+fn probe(world: &World) -> Coordinate {
+    match world.ants.first() {
+        Some(a) => a.position.clone(),
+        None => world.colonies[0].home_position.clone(),
+    }
+}
+
+fn navigable(c: &mut Criterion) {
+    let world = colony(100);
+    let pos = probe(&world);
+    let scents: &Vec<Scent> = &world.colonies[0].home_scents;
+    let food: &Vec<Coordinate> = &world.food_positions[FoodKind::Sugar];
+
+    let mut group = c.benchmark_group("navigable");
+    group.bench_function("scents/get_nearest", |b| {
+        b.iter(|| scents.get_nearest(black_box(&pos), 15.0, 1.0, 90.0))
+    });
+    group.bench_function("scents/get_avg_direction", |b| {
+        b.iter(|| scents.get_avg_direction(black_box(&pos), 15.0, 1.0, 90.0))
+    });
+    group.bench_function("coordinates/get_nearest", |b| {
+        b.iter(|| food.get_nearest(black_box(&pos), 15.0, 1.0, 90.0))
+    });
+    group.bench_function("coordinates/get_avg_direction", |b| {
+        b.iter(|| food.get_avg_direction(black_box(&pos), 15.0, 1.0, 90.0))
+    });
+    group.finish();
+}
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    group.sample_size(20);
+    for size in COLONY_SIZES.iter() {
+        let mut world = colony(*size);
+        // rewound before every step so that each sample measures the same tick
+        let start = History::new(&world);
+        let tick = world.tick;
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            b.iter_custom(|iters| {
+                let mut total = Duration::default();
+                for _ in 0..iters {
+                    start.seek(&mut world, tick);
+                    let began = Instant::now();
+                    world.step();
+                    total += began.elapsed();
+                }
+                total
+            })
+        });
+    }
+    group.finish();
+}
+
+fn scent_decay(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scent_decay");
+    for size in COLONY_SIZES.iter() {
+        let world = colony(*size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            b.iter_batched_ref(
+                || {
+                    let mut w = World::new();
+                    w.colonies = world.colonies.clone();
+                    w
+                },
+                |w| w.decay_scents(),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn food_lookup(c: &mut Criterion) {
+    let world = colony(10);
+    let pos = probe(&world);
+    let food = &world.food_positions[FoodKind::Sugar];
+    let on_food = food[0].clone();
+
+    let mut group = c.benchmark_group("food_lookup");
+    group.bench_function("miss", |b| {
+        b.iter(|| world::reachable_food(black_box(&pos), food))
+    });
+    group.bench_function("hit", |b| {
+        b.iter(|| world::reachable_food(black_box(&on_food), food))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, navigable, tick, scent_decay, food_lookup);
+criterion_main!(benches);
@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::ant::ANT_DETECTION_RANGE;
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::scent::{Scent, SCENT_LIFE};
+use crate::world::{World, HOME_SIZE, X_SIZE, Y_SIZE};
+
+/// Number of food scents an ant needs around it to count as being on a trail, this needs to be
+/// comfortably more than a lone ant leaves behind itself within its detection range
+const TRAIL_DENSITY: usize = 30;
+/// Fraction of ants which need to be on a trail for trails to count as formed
+const TRAIL_FRACTION: f32 = 0.25;
+/// Size of the cells scents are binned into when measuring entropy
+const ENTROPY_CELL: f32 = 20.0;
+
+/// A trip from picking up food to dropping it at home
+struct Trip {
+    start_tick: u64,
+    start: Coordinate,
+    last: Coordinate,
+    length: f32,
+}
+
+/// Collects foraging and trail statistics over a run, `record` should be called after every tick
+pub struct Analytics {
+    trips: HashMap<u64, Trip>,
+    trips_completed: u32,
+    total_length: f32,
+    total_duration: u64,
+    total_straightness: f32,
+    trail_formed_at: Option<u64>,
+    trail_fraction_sum: f32,
+    trail_fraction: f32,
+    entropy_sum: f32,
+    entropy: f32,
+    ticks: u64,
+}
+
+impl Analytics {
+    pub fn new() -> Analytics {
+        Analytics {
+            trips: HashMap::new(),
+            trips_completed: 0,
+            total_length: 0.0,
+            total_duration: 0,
+            total_straightness: 0.0,
+            trail_formed_at: None,
+            trail_fraction_sum: 0.0,
+            trail_fraction: 0.0,
+            entropy_sum: 0.0,
+            entropy: 0.0,
+            ticks: 0,
+        }
+    }
+
+    pub fn record(&mut self, world: &World) {
+        self.ticks += 1;
+
+        // forget trips for ants which are no longer around
+        let alive: HashSet<u64> = world.ants.iter().map(|a| a.id).collect();
+        self.trips.retain(|id, _| alive.contains(id));
+
+        for a in world.ants.iter() {
+            match (a.has_food, self.trips.get_mut(&a.id)) {
+                (true, Some(trip)) => {
+                    trip.length += trip.last.dist(&a.position);
+                    trip.last = a.position.clone();
+                }
+                (true, None) => {
+                    self.trips.insert(
+                        a.id,
+                        Trip {
+                            start_tick: world.tick,
+                            start: a.position.clone(),
+                            last: a.position.clone(),
+                            length: 0.0,
+                        },
+                    );
+                }
+                (false, Some(_)) => {
+                    // SAFE: the match guarantees there is a trip for this ant
+                    let trip = self.trips.remove(&a.id).unwrap();
+                    let length = trip.length + trip.last.dist(&a.position);
+                    let home = &world.colonies[a.colony].home_position;
+                    let direct = (trip.start.dist(home) - HOME_SIZE).max(0.0);
+                    self.trips_completed += 1;
+                    self.total_length += length;
+                    self.total_duration += world.tick - trip.start_tick;
+                    self.total_straightness += if length > 0.0 {
+                        (direct / length).min(1.0)
+                    } else {
+                        1.0
+                    };
+                }
+                (false, None) => {}
+            }
+        }
+
+        self.trail_fraction = trail_fraction(world);
+        self.trail_fraction_sum += self.trail_fraction;
+        if self.trail_formed_at.is_none() && self.trail_fraction >= TRAIL_FRACTION {
+            self.trail_formed_at = Some(world.tick);
+        }
+
+        self.entropy = scent_entropy(world.colonies.iter().flat_map(|c| {
+            c.home_scents
+                .iter()
+                .chain(c.food_scents.sugar.iter())
+                .chain(c.food_scents.protein.iter())
+        }));
+        self.entropy_sum += self.entropy;
+    }
+
+    pub fn report(&self) -> Report {
+        let trips = self.trips_completed.max(1) as f32;
+        let ticks = self.ticks.max(1) as f32;
+        Report {
+            ticks: self.ticks,
+            trips_completed: self.trips_completed,
+            average_trip_length: self.total_length / trips,
+            average_trip_duration: self.total_duration as f32 / trips,
+            average_straightness: self.total_straightness / trips,
+            trail_formed_at: self.trail_formed_at,
+            average_trail_fraction: self.trail_fraction_sum / ticks,
+            final_trail_fraction: self.trail_fraction,
+            average_entropy: self.entropy_sum / ticks,
+            final_entropy: self.entropy,
+        }
+    }
+}
+
+impl Default for Analytics {
+    fn default() -> Analytics {
+        Analytics::new()
+    }
+}
+
+/// Summary of a run, produced by `Analytics::report`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub ticks: u64,
+    pub trips_completed: u32,
+    pub average_trip_length: f32,
+    /// in ticks
+    pub average_trip_duration: f32,
+    /// direct distance to the nest over the distance walked, 1.0 is a straight line
+    pub average_straightness: f32,
+    /// the first tick on which enough ants were on a trail
+    pub trail_formed_at: Option<u64>,
+    pub average_trail_fraction: f32,
+    pub final_trail_fraction: f32,
+    /// how spread out scents are, 0.0 is all in one place and 1.0 is evenly spread
+    pub average_entropy: f32,
+    pub final_entropy: f32,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ticks:                 {}", self.ticks)?;
+        writeln!(f, "trips completed:       {}", self.trips_completed)?;
+        writeln!(f, "average trip length:   {:.1}", self.average_trip_length)?;
+        writeln!(
+            f,
+            "average trip duration: {:.1} ticks",
+            self.average_trip_duration
+        )?;
+        writeln!(f, "average straightness:  {:.2}", self.average_straightness)?;
+        match self.trail_formed_at {
+            Some(t) => writeln!(f, "trail formed at:       tick {}", t)?,
+            None => writeln!(f, "trail formed at:       never")?,
+        }
+        writeln!(
+            f,
+            "ants on trails:        {:.1}% (final {:.1}%)",
+            self.average_trail_fraction * 100.0,
+            self.final_trail_fraction * 100.0
+        )?;
+        write!(
+            f,
+            "scent entropy:         {:.2} (final {:.2})",
+            self.average_entropy, self.final_entropy
+        )
+    }
+}
+
+/// The square `ANT_DETECTION_RANGE` across which `pos` falls in
+fn square(pos: &Coordinate) -> (i32, i32) {
+    (
+        (pos.x / ANT_DETECTION_RANGE).floor() as i32,
+        (pos.y / ANT_DETECTION_RANGE).floor() as i32,
+    )
+}
+
+/// Fraction of ants with enough scent from their own food trail around them to be following it
+fn trail_fraction(world: &World) -> f32 {
+    if world.ants.is_empty() {
+        return 0.0;
+    }
+    // food scents by colony, food and square, so each ant only looks at those in the squares
+    // around it
+    let mut squares: HashMap<(usize, FoodKind, (i32, i32)), Vec<&Coordinate>> = HashMap::new();
+    for c in world.colonies.iter() {
+        for kind in FoodKind::ALL.iter() {
+            for s in c.food_scents[*kind].iter() {
+                squares
+                    .entry((c.id, *kind, square(&s.position)))
+                    .or_default()
+                    .push(&s.position);
+            }
+        }
+    }
+    let on_trail = world
+        .ants
+        .iter()
+        .filter(|a| {
+            let (x, y) = square(&a.position);
+            let mut near = 0;
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if let Some(scents) = squares.get(&(a.colony, a.food_kind, (x + dx, y + dy))) {
+                        near += scents
+                            .iter()
+                            .filter(|s| s.dist(&a.position) < ANT_DETECTION_RANGE)
+                            .count();
+                    }
+                }
+            }
+            near >= TRAIL_DENSITY
+        })
+        .count();
+    on_trail as f32 / world.ants.len() as f32
+}
+
+/// Normalised shannon entropy of scent strength binned over the map
+fn scent_entropy<'a, I>(scents: I) -> f32
+where
+    I: Iterator<Item = &'a Scent>,
+{
+    let columns = (X_SIZE / ENTROPY_CELL).ceil() as usize;
+    let rows = (Y_SIZE / ENTROPY_CELL).ceil() as usize;
+    let mut cells = vec![0.0; columns * rows];
+    for s in scents {
+        let x = ((s.position.x / ENTROPY_CELL) as usize).min(columns - 1);
+        let y = ((s.position.y / ENTROPY_CELL) as usize).min(rows - 1);
+        cells[y * columns + x] += s.life.min(SCENT_LIFE) as f32;
+    }
+
+    let total: f32 = cells.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let entropy: f32 = cells
+        .iter()
+        .filter(|c| **c > 0.0)
+        .map(|c| {
+            let p = c / total;
+            -p * p.ln()
+        })
+        .sum();
+    entropy / (cells.len() as f32).ln()
+}
+
+#[test]
+fn scent_entropy_bounds() {
+    let clumped: Vec<Scent> = (0..10).map(|_| Scent::new(10.0, 10.0, 0.0)).collect();
+    assert_eq!(scent_entropy(clumped.iter()), 0.0);
+
+    let mut spread = vec![];
+    let mut y = ENTROPY_CELL / 2.0;
+    while y < Y_SIZE {
+        let mut x = ENTROPY_CELL / 2.0;
+        while x < X_SIZE {
+            spread.push(Scent::new(x, y, 0.0));
+            x += ENTROPY_CELL;
+        }
+        y += ENTROPY_CELL;
+    }
+    assert!((scent_entropy(spread.iter()) - 1.0).abs() < 0.001);
+}
+
+#[test]
+fn delivered_trips_are_measured() {
+    let mut world = World::builder().seed(1).empty().build();
+    world.spawn_ant(0);
+    let mut analytics = Analytics::new();
+    let home = world.colonies[0].home_position.clone();
+    // out and round the houses on the way back, 100 walked for 35 as the crow flies
+    let path = [(0.0, 50.0), (30.0, 50.0), (30.0, 10.0), (0.0, 10.0)];
+    for (i, (x, y)) in path.iter().enumerate() {
+        world.tick += 1;
+        let a = &mut world.ants[0];
+        a.position = Coordinate::new(home.x + x, home.y + y);
+        a.has_food = i + 1 < path.len();
+        analytics.record(&world);
+    }
+
+    let report = analytics.report();
+    assert_eq!(report.trips_completed, 1);
+    assert!((report.average_trip_length - 100.0).abs() < 0.001);
+    assert_eq!(report.average_trip_duration, 3.0);
+    assert!((report.average_straightness - 0.35).abs() < 0.001);
+}
@@ -1,80 +1,132 @@
-#![feature(drain_filter)]
-#![feature(destructuring_assignment)]
-
-use crate::navigable::Navigable;
-use crate::scent::Scent;
-use crate::{coordinate::Coordinate, Bounded};
+use crate::brain::AntBrain;
+use crate::combat::Caste;
+use crate::coordinate::Coordinate;
+use crate::corpse::Corpse;
+use crate::food::FoodKind;
+use crate::hex::{self, HexGrid};
+use crate::rng;
+#[cfg(feature = "viewer")]
 use ggez::graphics::{GlBackendSpec, ImageGeneric, MeshBuilder};
 use rand::prelude::*;
+use std::rc::Rc;
 
 const ANT_SPEED: f32 = 1.0;
 const SCENT_LIFE: u32 = 300;
-const ANT_DETECTION_RANGE: f32 = 15.0;
+pub const ANT_DETECTION_RANGE: f32 = 15.0;
 const HOME_SIZE: f32 = 25.0;
 
 const X_SIZE: f32 = 500.0;
 const Y_SIZE: f32 = 500.0;
 
-pub struct Ant<'a> {
+/// Distance walked between each frame of the walking animation
+#[cfg(feature = "viewer")]
+const STRIDE: f32 = 4.0;
+
+#[derive(Clone)]
+pub struct Ant {
+    pub id: u64,
     pub position: Coordinate,
     pub direction: f32, // angle 0 -> 359
     pub has_food: bool,
+    /// the kind of food we are looking for, or carrying if we have food
+    pub food_kind: FoodKind,
+    /// a dead nestmate we are taking to the midden
+    pub corpse: Option<Corpse>,
+    /// index of the colony we belong to
+    pub colony: usize,
+    pub caste: Caste,
+    /// goes down when bitten, the ant dies once it runs out
+    pub health: f32,
     pub speed: f32,
     pub life: u32,
-    pub keyframe: usize,
-    frames: &'a [ImageGeneric<GlBackendSpec>],
+    /// where we were at the start of the current tick, used to smooth rendering
+    pub previous_position: Coordinate,
+    pub previous_direction: f32,
+    /// total distance walked, drives the walking animation
+    pub distance_walked: f32,
+    /// life owed from climbing, taken off once it adds up to a whole tick
+    pub fatigue: f32,
+    /// ticks left holding a bridge over water for others to cross, we can't move until then
+    pub bridging: u32,
+    pub brain: Rc<dyn AntBrain>,
 }
 
-impl<'a> Ant<'a> {
-    pub fn new(x: f32, y: f32, frames: &'a [ImageGeneric<GlBackendSpec>]) -> Ant {
-        let mut r = rand::thread_rng();
-        let dir: f32 = r.gen::<f32>();
+impl Ant {
+    pub fn new(id: u64, x: f32, y: f32, brain: Rc<dyn AntBrain>) -> Ant {
+        let dir: f32 = rng::with(|r| r.gen::<f32>()) * 359.9;
         Ant {
+            id,
             position: Coordinate::new(x, y),
-            direction: dir * 359.9,
+            direction: dir,
             has_food: false,
+            food_kind: FoodKind::Sugar,
+            corpse: None,
+            colony: 0,
+            caste: Caste::Worker,
+            health: Caste::Worker.max_health(),
             speed: ANT_SPEED,
             life: 4000,
-            keyframe: 0,
-            frames: frames,
+            previous_position: Coordinate::new(x, y),
+            previous_direction: dir,
+            distance_walked: 0.0,
+            fatigue: 0.0,
+            bridging: 0,
+            brain,
         }
     }
 
-    /// Try to get closer to the next target
-    ///
-    /// # Arguments
-    /// - `targets` things which we want to go to, should be prioritied over waypoints
-    /// - `waypoints` things which direct us to targets
-    pub fn traverse<T, U>(&mut self, targets: Option<&T>, waypoints: &U) -> ()
-    where
-        T: Navigable,
-        U: Navigable,
-    {
-        if let Some(targets) = targets {
-            if let Some(p) = targets.get_nearest(
-                &self.position,
-                ANT_DETECTION_RANGE,
-                self.speed,
-                self.direction,
-            ) {
-                self.direction = self.position.direction(&p);
-                self.position = self.position.traverse_direction(self.direction, self.speed);
-                return;
-            }
+    /// Walk forwards in the direction we are facing
+    pub fn walk(&mut self) {
+        self.stride(self.speed);
+    }
+
+    /// Walk `distance` forwards in the direction we are facing
+    pub fn stride(&mut self, distance: f32) {
+        self.position = self.position.traverse_direction(self.direction, distance);
+        self.distance_walked += distance;
+    }
+
+    /// Walk forwards on a hex grid, facing the closest of the six ways out of the cell and
+    /// hopping to the next cell once we have walked far enough to reach it
+    pub fn hop(&mut self, grid: &HexGrid, distance: f32) {
+        let heading = hex::heading(self.direction);
+        self.direction = hex::HEADINGS[heading];
+        let before = (self.distance_walked / grid.spacing()).floor();
+        self.distance_walked += distance;
+        if (self.distance_walked / grid.spacing()).floor() > before {
+            self.position = grid.centre(grid.cell(&self.position).neighbour(heading));
         }
+    }
 
-        let direction = waypoints.get_avg_direction(
-            &self.position,
-            ANT_DETECTION_RANGE,
-            self.speed,
-            self.direction,
-        );
-        self.direction = direction;
-        let pos = self.position.clone();
-        self.position = self.position.traverse_direction(self.direction, self.speed);
-        self.direction = pos.direction(&self.position);
+    /// Remember where we are, should be called at the start of every tick
+    pub fn begin_tick(&mut self) {
+        self.previous_position = self.position.clone();
+        self.previous_direction = self.direction;
+    }
+
+    /// Where to draw the ant, `alpha` is how far through the current tick we are from 0 -> 1
+    pub fn interpolated_position(&self, alpha: f32) -> Coordinate {
+        Coordinate::new(
+            self.previous_position.x + (self.position.x - self.previous_position.x) * alpha,
+            self.previous_position.y + (self.position.y - self.previous_position.y) * alpha,
+        )
+    }
+
+    /// Which way to draw the ant facing, turning the short way round
+    pub fn interpolated_direction(&self, alpha: f32) -> f32 {
+        let mut diff = self.direction - self.previous_direction;
+        while diff > 180.0 {
+            diff -= 360.0;
+        }
+        while diff < -180.0 {
+            diff += 360.0;
+        }
+        self.previous_direction + diff * alpha
     }
+}
 
+#[cfg(feature = "viewer")]
+impl Ant {
     /*pub fn draw<'b>(&mut self, mesh: &'b mut MeshBuilder) -> &'b mut MeshBuilder {
         let output = mesh.circle(
             ggez::graphics::DrawMode::Stroke(ggez::graphics::StrokeOptions::DEFAULT),
@@ -98,10 +150,10 @@ impl<'a> Ant<'a> {
             ggez::graphics::Color::from_rgb(220, 15, 0),
         )*/
     }*/
-    pub fn draw_debug<'b>(&mut self, mesh: &'b mut MeshBuilder) -> &'b mut MeshBuilder {
+    pub fn draw_debug<'b>(&self, mesh: &'b mut MeshBuilder, alpha: f32) -> &'b mut MeshBuilder {
         mesh.circle(
             ggez::graphics::DrawMode::Stroke(ggez::graphics::StrokeOptions::DEFAULT),
-            self.position.clone(),
+            self.interpolated_position(alpha),
             ANT_DETECTION_RANGE as f32,
             0.1,
             ggez::graphics::Color::from_rgb(255, 0, 0),
@@ -121,27 +173,29 @@ impl<'a> Ant<'a> {
             ggez::graphics::Color::from_rgb(220, 15, 0),
         )*/
     }
-    pub fn draw<'b>(&mut self, ctx: &mut ggez::Context) {
+    pub fn draw(
+        &self,
+        ctx: &mut ggez::Context,
+        frames: &[ImageGeneric<GlBackendSpec>],
+        alpha: f32,
+    ) {
+        let position = self.interpolated_position(alpha);
         let params = ggez::graphics::DrawParam::default()
             .offset(ggez::mint::Vector2 { x: 0.5, y: 0.5 })
-            .rotation(self.direction)
+            .rotation(self.interpolated_direction(alpha).to_radians())
             .scale(ggez::mint::Vector2 { y: 1.0, x: 1.0 })
             .dest(ggez::mint::Vector2 {
-                y: self.position.y * 2.0,
-                x: self.position.x * 2.0,
+                y: position.y * 2.0,
+                x: position.x * 2.0,
             })
             .color(ggez::graphics::Color::from_rgb(255, 0, 255));
+        let keyframe = (self.distance_walked / STRIDE) as usize;
         ggez::graphics::draw(
             ctx,
-            &self
-                .frames
-                .get(self.keyframe % self.frames.len())
-                .unwrap()
-                .clone(),
+            &frames.get(keyframe % frames.len()).unwrap().clone(),
             params,
         )
         .unwrap();
-        self.keyframe += 1;
         /*
         let output = mesh.circle(
             ggez::graphics::DrawMode::Stroke(ggez::graphics::StrokeOptions::DEFAULT),
@@ -166,3 +220,16 @@ impl<'a> Ant<'a> {
         )*/
     }
 }
+
+#[test]
+fn ant_interpolation() {
+    let mut a = Ant::new(0, 0.0, 0.0, Rc::new(crate::brain::DefaultBrain));
+    a.direction = 350.0;
+    a.begin_tick();
+    a.direction = 10.0;
+    a.walk();
+
+    let halfway = a.interpolated_position(0.5);
+    assert!((halfway.x - 0.5 * a.position.x).abs() < 0.001);
+    assert!((a.interpolated_direction(0.5) - 360.0).abs() < 0.001);
+}
@@ -0,0 +1,57 @@
+//! Runs scenario files without a window and reports which passed
+//!
+//! cargo run --bin scenario -- [--log-level LEVEL] [--log-json PATH] scenarios/*.toml
+
+use std::path::Path;
+use std::process;
+
+use tracing::{error, warn};
+
+use ants::logging;
+use ants::scenario::Scenario;
+
+pub fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut paths: Vec<String> = vec![];
+    let (mut level, mut json) = (None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--log-level" => level = args.next(),
+            "--log-json" => json = args.next(),
+            _ => paths.push(arg),
+        }
+    }
+    logging::init(level.as_deref(), json.as_deref().map(Path::new))
+        .expect("unable to set up logging");
+    if paths.is_empty() {
+        eprintln!("usage: scenario [--log-level LEVEL] [--log-json PATH] FILE...");
+        process::exit(2);
+    }
+
+    let mut failed = 0;
+    for path in paths.iter() {
+        match Scenario::load(Path::new(path)) {
+            Ok(scenario) => {
+                let outcome = scenario.run();
+                println!("{}", outcome);
+                if !outcome.passed() {
+                    warn!(name = %outcome.name, "scenario failed");
+                    failed += 1;
+                }
+            }
+            Err(e) => {
+                error!(path = %path, "unable to load scenario: {}", e);
+                println!("FAIL {}: {}", path, e);
+                failed += 1;
+            }
+        }
+    }
+    println!(
+        "{} of {} scenarios passed",
+        paths.len() - failed,
+        paths.len()
+    );
+    if failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,575 @@
+use std::time::Duration;
+use std::vec;
+
+use ggez::{
+    conf::Conf,
+    conf::NumSamples,
+    conf::WindowMode,
+    conf::WindowSetup,
+    event,
+    event::{KeyCode, KeyMods},
+    graphics::{GlBackendSpec, ImageGeneric, Mesh, MeshBuilder},
+    timer, Context, ContextBuilder, GameResult,
+};
+use rand::prelude::*;
+use rand_distr::Normal;
+use tracing::{error, info};
+
+use ants::analytics::Analytics;
+use ants::config::Config;
+use ants::coordinate::Coordinate;
+use ants::corpse::NECROMONE_LIFE;
+use ants::food::FoodKind;
+use ants::history::History;
+use ants::logging;
+use ants::navigable::Navigable;
+use ants::profiler::Phase;
+use ants::scenario::Scenario;
+use ants::scent::Scent;
+#[cfg(feature = "server")]
+use ants::server::Server;
+use ants::terrain::Heightmap;
+use ants::water;
+use ants::world::{World, HOME_SIZE, X_SIZE, Y_SIZE};
+
+const ANT_DETECTION_RANGE: f32 = 50.0;
+
+/// How much time each tick of the simulation covers
+const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 30);
+/// Most ticks to run in one frame before giving up on catching up
+const MAX_TICKS_PER_FRAME: u32 = 5;
+/// Ticks to jump when scrubbing through history with shift held
+const SCRUB_JUMP: u64 = 30;
+/// Squares across each side of the map used to shade the ground
+const TERRAIN_CELLS: usize = 50;
+
+struct State<'a> {
+    dt: std::time::Duration,
+    /// time which has passed but not yet been simulated
+    accumulator: Duration,
+    world: World,
+    history: History,
+    /// when paused the user can scrub back and forth through the history
+    paused: bool,
+    /// show how long each phase of a tick and frame is taking
+    show_profile: bool,
+    ant_frames: &'a [ImageGeneric<GlBackendSpec>],
+    anthill: ImageGeneric<GlBackendSpec>,
+    /// lets other programs watch and steer the simulation
+    #[cfg(feature = "server")]
+    server: Option<Server>,
+}
+
+impl<'a> State<'a> {
+    pub fn new(
+        world: World,
+        anthill: ImageGeneric<GlBackendSpec>,
+        ant_frames: &'a [ImageGeneric<GlBackendSpec>],
+    ) -> State<'a> {
+        State {
+            dt: std::time::Duration::new(0, 0),
+            accumulator: Duration::new(0, 0),
+            history: History::new(&world),
+            world,
+            paused: false,
+            show_profile: false,
+            ant_frames: ant_frames,
+            anthill,
+            #[cfg(feature = "server")]
+            server: None,
+        }
+    }
+}
+
+impl<'a> ggez::event::EventHandler for State<'a> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.dt = timer::delta(ctx);
+        #[cfg(feature = "server")]
+        if let Some(server) = &self.server {
+            let paused = self.paused;
+            server.serve(&mut self.world, &mut self.paused);
+            if paused && !self.paused {
+                self.history.truncate(self.world.tick);
+                self.accumulator = Duration::new(0, 0);
+            }
+        }
+        if self.paused {
+            return Ok(());
+        }
+        self.accumulator += self.dt;
+        let mut ticks = 0;
+        while self.accumulator >= TIMESTEP {
+            self.history.step(&mut self.world);
+            exit_on_violation(&self.world);
+            #[cfg(feature = "server")]
+            if let Some(server) = &self.server {
+                server.broadcast(&self.world);
+            }
+            self.accumulator -= TIMESTEP;
+            ticks += 1;
+            if ticks == MAX_TICKS_PER_FRAME {
+                // we have fallen too far behind, so drop the time rather than spiral
+                self.accumulator = Duration::new(0, 0);
+            }
+        }
+        Ok(())
+    }
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods, _: bool) {
+        let jump = if keymods.contains(KeyMods::SHIFT) {
+            SCRUB_JUMP
+        } else {
+            1
+        };
+        match keycode {
+            KeyCode::Escape => event::quit(ctx),
+            KeyCode::Space => {
+                if self.paused {
+                    // carry on from wherever we have scrubbed to
+                    self.history.truncate(self.world.tick);
+                    self.accumulator = Duration::new(0, 0);
+                }
+                self.paused = !self.paused;
+            }
+            KeyCode::P => {
+                self.show_profile = !self.show_profile;
+                let profiler = &mut self.world.profiler;
+                profiler.enabled = self.show_profile || profiler.trace;
+            }
+            KeyCode::Left if self.paused => {
+                let tick = self
+                    .world
+                    .tick
+                    .saturating_sub(jump)
+                    .max(self.history.oldest_tick());
+                self.history.seek(&mut self.world, tick);
+            }
+            KeyCode::Right if self.paused => {
+                let tick = (self.world.tick + jump).min(self.history.newest_tick());
+                self.history.seek(&mut self.world, tick);
+            }
+            _ => {}
+        }
+    }
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // how far we are between the last tick and the next one
+        let alpha = self.accumulator.as_secs_f32() / TIMESTEP.as_secs_f32();
+        let started = self.world.profiler.start();
+        let mut scene = &mut ggez::graphics::MeshBuilder::new();
+
+        // shade the ground, darker in the hollows and lighter up high
+        if let Some(terrain) = self.world.terrain.as_ref() {
+            let cell = X_SIZE / TERRAIN_CELLS as f32;
+            for i in 0..TERRAIN_CELLS * TERRAIN_CELLS {
+                let x = (i % TERRAIN_CELLS) as f32 * cell;
+                let y = (i / TERRAIN_CELLS) as f32 * cell;
+                let h = terrain
+                    .map
+                    .at(&Coordinate::new(x + cell / 2.0, y + cell / 2.0));
+                let shade = (130.0 + h * 110.0) as u8;
+                scene = scene.rectangle(
+                    ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                    ggez::graphics::Rect::new(x, y, cell, cell),
+                    ggez::graphics::Color::from_rgb(shade, shade - 10, shade - 30),
+                );
+            }
+        }
+        /*
+        scene.circle(
+            ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+            self.world.colonies[0].home_position.clone(),
+            HOME_SIZE as f32,
+            1.0,
+            ggez::graphics::Color::from_rgb(46, 19, 0),
+        );*/
+
+        for pond in self.world.water.iter() {
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                pond.centre.clone(),
+                pond.radius,
+                0.5,
+                ggez::graphics::Color::from_rgb(60, 110, 200),
+            );
+        }
+
+        for wall in self.world.walls.iter() {
+            scene = scene.line(
+                &[wall.from.clone(), wall.to.clone()],
+                2.0,
+                ggez::graphics::Color::from_rgb(90, 90, 90),
+            )?;
+        }
+
+        for colony in self.world.colonies.iter() {
+            for (kind, scents) in colony.trails() {
+                let pheromone = self.world.config.pheromone(kind);
+                let (r, g, b) = pheromone.colour;
+                for s in scents.iter() {
+                    // reinforced scents grow up to twice the size of a fresh one
+                    let strength = (s.life as f32 / pheromone.life.max(1) as f32).min(2.0);
+                    scene = scene.circle(
+                        ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                        s.position.clone(),
+                        2.5 * strength,
+                        1.0,
+                        ggez::graphics::Color::from_rgb(r, g, b),
+                    );
+                }
+            }
+        }
+
+        for n in self.world.necromone.iter() {
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                n.position.clone(),
+                3.0 * (n.life as f32 / NECROMONE_LIFE as f32),
+                1.0,
+                ggez::graphics::Color::from_rgb(120, 0, 140),
+            );
+        }
+
+        for colony in self.world.colonies.iter() {
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Stroke(ggez::graphics::StrokeOptions::DEFAULT),
+                colony.midden_position.clone(),
+                HOME_SIZE,
+                0.1,
+                ggez::graphics::Color::from_rgb(90, 60, 30),
+            );
+        }
+        for c in self.world.corpses.iter() {
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                c.position.clone(),
+                3.0,
+                1.0,
+                ggez::graphics::Color::from_rgb(40, 20, 10),
+            );
+        }
+
+        for kind in FoodKind::ALL.iter() {
+            let food_colour = match kind {
+                FoodKind::Sugar => ggez::graphics::Color::from_rgb(15, 200, 15),
+                FoodKind::Protein => ggez::graphics::Color::from_rgb(200, 30, 30),
+            };
+
+            for f in self.world.food_positions[*kind].iter_mut() {
+                scene = scene.circle(
+                    ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                    f.clone(),
+                    5 as f32,
+                    1.0,
+                    food_colour,
+                );
+            }
+        }
+
+        for a in self.world.ants.iter() {
+            if a.bridging > 0 {
+                scene = scene.circle(
+                    ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                    a.position.clone(),
+                    water::BRIDGE_SPAN,
+                    0.5,
+                    ggez::graphics::Color::from_rgb(110, 70, 30),
+                );
+            }
+            scene = a.draw_debug(scene, alpha);
+            //a.draw(ctx);
+        }
+
+        for q in self.world.colonies.iter().filter_map(|c| c.queen.as_ref()) {
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                q.position.clone(),
+                4.0,
+                1.0,
+                ggez::graphics::Color::from_rgb(160, 0, 90),
+            );
+        }
+
+        // which way the wind is blowing in the middle of the map
+        let wind = &self.world.config.wind;
+        if wind.speed > 0.0 {
+            let centre = Coordinate::new(25.0, 25.0);
+            let (direction, speed) = wind.at(&centre, self.world.tick);
+            let tip = centre.traverse_direction(direction, 5.0 + speed * 10.0);
+            scene = scene
+                .line(
+                    &[centre.clone(), tip.clone()],
+                    1.0,
+                    ggez::graphics::Color::from_rgb(255, 255, 255),
+                )
+                .unwrap();
+            scene = scene.circle(
+                ggez::graphics::DrawMode::Fill(ggez::graphics::FillOptions::DEFAULT),
+                tip,
+                2.0,
+                0.1,
+                ggez::graphics::Color::from_rgb(255, 255, 255),
+            );
+        }
+
+        let scene = scene.build(ctx).unwrap();
+        self.world.profiler.stop(Phase::MeshBuild, started);
+        let started = self.world.profiler.start();
+        ggez::graphics::clear(ctx, ggez::graphics::Color::from_rgb(200, 200, 200));
+        for colony in self.world.colonies.iter() {
+            let params = ggez::graphics::DrawParam::default()
+                .offset(ggez::mint::Vector2 { x: 0.5, y: 0.5 })
+                .scale(ggez::mint::Vector2 { y: 2.0, x: 2.0 })
+                .dest(ggez::mint::Vector2 {
+                    y: colony.home_position.y * 2.0,
+                    x: colony.home_position.x * 2.0,
+                });
+            ggez::graphics::draw(ctx, &self.anthill, params).unwrap();
+        }
+        let mut params = ggez::graphics::DrawParam::default();
+        params.scale = ggez::mint::Vector2 {
+            x: 2.0 as f32,
+            y: 2.0 as f32,
+        };
+        ggez::graphics::draw(ctx, &scene, params).unwrap();
+        for a in self.world.ants.iter() {
+            //scene = a.draw(scene);
+            a.draw(ctx, self.ant_frames, alpha);
+        }
+        if self.show_profile {
+            draw_profile(ctx, &self.world)?;
+        }
+        ggez::graphics::present(ctx).unwrap();
+        self.world.profiler.stop(Phase::Draw, started);
+        self.world.profiler.finish();
+        Ok(())
+    }
+}
+
+/// List the average time each phase takes in the top left corner
+fn draw_profile(ctx: &mut Context, world: &World) -> GameResult {
+    let mut lines = vec![format!("tick {}  ants {}", world.tick, world.ants.len())];
+    for (phase, took) in world.profiler.averages() {
+        lines.push(format!(
+            "{:<18} {:>7.3} ms",
+            phase.name(),
+            took.as_secs_f64() * 1000.0
+        ));
+    }
+    let text = ggez::graphics::Text::new(lines.join("\n"));
+    let params = ggez::graphics::DrawParam::default()
+        .dest(ggez::mint::Point2 { x: 10.0, y: 10.0 })
+        .color(ggez::graphics::BLACK);
+    ggez::graphics::draw(ctx, &text, params)
+}
+
+/// Get the value following `flag` on the command line, if it was given
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+}
+
+/// Stop at the first thing `--check-invariants` finds wrong, the world has already logged it
+fn exit_on_violation(world: &World) {
+    if world.violation.is_some() {
+        std::process::exit(1);
+    }
+}
+
+/// Run the simulation without a window for `ticks` ticks, then report on how it went
+///
+/// `after_tick` is handed the world after every tick
+fn run_headless(mut world: World, ticks: u64, mut after_tick: impl FnMut(&mut World)) -> World {
+    let mut analytics = Analytics::new();
+    for _ in 0..ticks {
+        world.step();
+        exit_on_violation(&world);
+        analytics.record(&world);
+        after_tick(&mut world);
+    }
+    println!("{}", analytics.report());
+    if world.profiler.enabled {
+        // nothing is drawn, so only the simulation phases have run
+        for (phase, took) in world.profiler.averages() {
+            if took.as_nanos() > 0 {
+                println!(
+                    "{:<22} {:.3} ms",
+                    format!("{}:", phase.name()),
+                    took.as_secs_f64() * 1000.0
+                );
+            }
+        }
+    }
+    world
+}
+
+pub fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // --log-level takes anything RUST_LOG does, --log-json keeps a copy as JSON lines
+    logging::init(
+        arg_value(&args, "--log-level").map(|l| l.as_str()),
+        arg_value(&args, "--log-json").map(std::path::Path::new),
+    )
+    .expect("unable to set up logging");
+
+    let mut builder = World::builder();
+    if let Some(seed) = arg_value(&args, "--seed") {
+        builder = builder.seed(seed.parse().expect("--seed must be a number"));
+    }
+    let mut config = Config::default();
+    // --wind SPEED[,DIRECTION]
+    if let Some(wind) = arg_value(&args, "--wind") {
+        let mut parts = wind
+            .split(',')
+            .map(|p| p.parse().expect("--wind must be SPEED[,DIRECTION]"));
+        config.wind.speed = parts.next().unwrap_or(0.0);
+        config.wind.direction = parts.next().unwrap_or(0.0);
+    }
+    if let Some(size) = arg_value(&args, "--hex") {
+        config.hex_size = size.parse().expect("--hex must be a cell size");
+    }
+    if let Some(ponds) = arg_value(&args, "--ponds") {
+        config.ponds = ponds.parse().expect("--ponds must be a number");
+    }
+    if let Some(hills) = arg_value(&args, "--hills") {
+        config.hills = hills.parse().expect("--hills must be a number");
+    }
+    if args.iter().any(|a| a == "--check-invariants") {
+        config.check_invariants = true;
+    }
+    if let Err(e) = config.validate() {
+        error!("invalid config: {}", e);
+        std::process::exit(1);
+    }
+    builder = builder.config(config);
+    // a greyscale image, the lighter the higher
+    if let Some(path) = arg_value(&args, "--heightmap") {
+        let map = match Heightmap::from_image(std::path::Path::new(path)) {
+            Ok(map) => map,
+            Err(e) => {
+                error!(path = %path, "unable to load heightmap: {}", e);
+                std::process::exit(1);
+            }
+        };
+        info!(path = %path, width = map.width, height = map.height, "heightmap loaded");
+        builder = builder.heightmap(map);
+    }
+    // a scenario file sets up the world in place of the options above
+    let mut world = match arg_value(&args, "--scenario") {
+        Some(path) => match Scenario::load(std::path::Path::new(path)) {
+            Ok(scenario) => scenario.build(),
+            Err(e) => {
+                error!(path = %path, "unable to load scenario: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => builder.build(),
+    };
+    // time every phase and keep the spans to write out as a Chrome trace at the end
+    let profile = arg_value(&args, "--profile").cloned();
+    if profile.is_some() {
+        world.profiler.enabled = true;
+        world.profiler.trace = true;
+    }
+    if let Some(path) = arg_value(&args, "--event-log") {
+        if let Err(e) = world.events.log_to(std::path::Path::new(path)) {
+            error!(path = %path, "unable to open the event log: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // --serve ADDR, e.g. 127.0.0.1:8080, for dashboards and tools to watch and steer the run,
+    // only on this machine unless --serve-remote is given too
+    #[cfg(feature = "server")]
+    let remote = args.iter().any(|a| a == "--serve-remote");
+    #[cfg(feature = "server")]
+    let server = arg_value(&args, "--serve").map(|addr| match Server::start(addr, remote) {
+        Ok(server) => {
+            println!("serving on http://{}", server.addr);
+            server
+        }
+        Err(e) => {
+            error!(addr = %addr, "unable to start the server: {}", e);
+            std::process::exit(1);
+        }
+    });
+
+    if let Some(ticks) = arg_value(&args, "--headless") {
+        #[cfg(feature = "server")]
+        let after_tick = |world: &mut World| {
+            if let Some(server) = &server {
+                // nothing else can pause a headless run, so it stays paused until told otherwise
+                let mut paused = false;
+                server.serve_while_paused(world, &mut paused);
+                server.broadcast(world);
+            }
+        };
+        #[cfg(not(feature = "server"))]
+        let after_tick = |_: &mut World| {};
+        let world = run_headless(
+            world,
+            ticks.parse().expect("--headless must be a number of ticks"),
+            after_tick,
+        );
+        if let Some(path) = profile {
+            world
+                .profiler
+                .write_chrome_trace(std::path::Path::new(&path))
+                .expect("unable to write --profile");
+        }
+        return;
+    }
+
+    let resource_dir = if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        let mut path = std::path::PathBuf::from(manifest_dir);
+        path.push("resources");
+        path
+    } else {
+        std::path::PathBuf::from("./resources")
+    };
+    let mut c = Conf::new();
+    c.window_setup = WindowSetup {
+        title: "Ant Simulator".to_owned(),
+        samples: NumSamples::Zero,
+        vsync: true,
+        icon: "".to_owned(),
+        srgb: true,
+    };
+    c.window_mode = WindowMode {
+        width: X_SIZE * 2.0,
+        height: Y_SIZE * 2.0,
+        maximized: false,
+        fullscreen_type: ggez::conf::FullscreenType::Windowed,
+        borderless: false,
+        min_width: X_SIZE * 2.0,
+        min_height: Y_SIZE * 2.0,
+        max_width: X_SIZE * 2.0,
+        max_height: Y_SIZE * 2.0,
+        resizable: false,
+    };
+    let (ref mut ctx, ref mut event_loop) = ContextBuilder::new("hello_ggez", "awesome_person")
+        .conf(c)
+        .add_resource_path(resource_dir)
+        .build()
+        .unwrap();
+
+    let anthill = ggez::graphics::Image::new(ctx, "/anthill.png").unwrap();
+
+    let ant_0 = ggez::graphics::Image::new(ctx, "/ant1.png").unwrap();
+    let ant_1 = ggez::graphics::Image::new(ctx, "/ant2.png").unwrap();
+
+    let ant_frames = &[ant_0, ant_1];
+    let mut state = State::new(world, anthill, ant_frames);
+    #[cfg(feature = "server")]
+    {
+        state.server = server;
+    }
+
+    event::run(ctx, event_loop, &mut state).unwrap();
+    if let Some(path) = profile {
+        state
+            .world
+            .profiler
+            .write_chrome_trace(std::path::Path::new(&path))
+            .expect("unable to write --profile");
+    }
+}
@@ -0,0 +1,447 @@
+use crate::ant::ANT_DETECTION_RANGE;
+use crate::combat::Caste;
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::navigable::Navigable;
+use crate::pheromone::{self, PheromoneKind, Response};
+use crate::scent::Scent;
+
+/// Something an ant can do on a tick, applied by the world in the order returned
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Face the given direction, angle 0 -> 359
+    Turn(f32),
+    /// Walk forwards at the ant's speed
+    Move,
+    /// Pick up the nearest food within reach, if any
+    PickUp,
+    /// Drop carried food into the home, if we are at it
+    Drop,
+    /// Pick up the nearest corpse within reach, if any
+    PickUpCorpse,
+    /// Leave a carried corpse in the midden, if we are at it
+    DropCorpse,
+    /// Bite the nearest ant from another colony, if one is close enough
+    Attack,
+    /// Leave a scent pointing back the way we are facing, alarms point the way we are facing
+    Deposit(PheromoneKind),
+}
+
+/// Everything an ant is able to sense about the world on a given tick
+pub struct Perception<'w> {
+    pub position: Coordinate,
+    /// where scents are smelt from, upwind of the ant when there is a breeze
+    pub nose: Coordinate,
+    pub direction: f32,
+    pub speed: f32,
+    pub has_food: bool,
+    /// the kind of food we are after, `food` and `food_scents` only cover this kind
+    pub food_kind: FoodKind,
+    pub caste: Caste,
+    /// true if the ant is close enough to home to drop food, this is worked out before the ant
+    /// moves so one carrying food drops it on the tick after it gets home
+    pub at_home: bool,
+    /// index into `food` of the closest food we can reach in one hop
+    pub reachable_food: Option<usize>,
+    pub carrying_corpse: bool,
+    /// true if the ant is close enough to the midden to drop a corpse
+    pub at_midden: bool,
+    /// true if there is a corpse we can reach in one hop
+    pub reachable_corpse: bool,
+    /// where the closest ant from another colony is, if we can see one
+    pub enemy: Option<Coordinate>,
+    /// true if that ant is close enough to bite
+    pub enemy_in_reach: bool,
+    pub home: &'w Coordinate,
+    pub midden: &'w Coordinate,
+    pub food: &'w Vec<Coordinate>,
+    pub home_scents: &'w Vec<Scent>,
+    pub food_scents: &'w Vec<Scent>,
+    /// laid by nestmates in a fight, each scent points towards the trouble
+    pub alarm_scents: &'w Vec<Scent>,
+    pub repellent_scents: &'w Vec<Scent>,
+    pub exploration_scents: &'w Vec<Scent>,
+    /// given off by corpses, each scent points back towards its corpse
+    pub necromone: &'w Vec<Scent>,
+    /// how the world is set up, including how ants respond to each pheromone
+    pub config: &'w Config,
+}
+
+/// Used to define a strategy which decides what an ant does each tick
+pub trait AntBrain {
+    /// Short identifier, used to tell strategies apart when comparing them
+    fn name(&self) -> &str;
+
+    /// Decide on the actions to take given what the ant can perceive
+    fn decide(&self, perception: &Perception) -> Vec<Action>;
+}
+
+/// Head for targets if we can see one, otherwise follow the waypoints
+///
+/// # Arguments
+/// - `targets` things which we want to go to, should be prioritied over waypoints
+/// - `waypoints` things which direct us to targets
+pub fn steer<T, U>(perception: &Perception, targets: Option<&T>, waypoints: &U) -> f32
+where
+    T: Navigable,
+    U: Navigable,
+{
+    if let Some(targets) = targets {
+        if let Some(p) = targets.get_nearest(
+            &perception.position,
+            ANT_DETECTION_RANGE,
+            perception.speed,
+            perception.direction,
+        ) {
+            return perception.position.direction(&p);
+        }
+    }
+
+    waypoints.get_avg_direction(
+        &perception.nose,
+        ANT_DETECTION_RANGE,
+        perception.speed,
+        perception.direction,
+    )
+}
+
+/// Which way to head given how the colony responds to a kind of pheromone, None if there is
+/// none of it close by or we take no notice of it
+pub fn respond(p: &Perception, scents: &Vec<Scent>, kind: PheromoneKind) -> Option<f32> {
+    match p.config.pheromone(kind).response {
+        Response::Follow => scents
+            .get_nearest(&p.nose, ANT_DETECTION_RANGE, p.speed, p.direction)
+            .map(|n| p.nose.direction(&n)),
+        // only what is in front matters, we are already walking away from anything behind
+        Response::Avoid => scents
+            .iter()
+            .filter(|s| s.position.dist(&p.nose) < ANT_DETECTION_RANGE && ahead(p, s))
+            .min_by(|a, b| {
+                a.position
+                    .dist(&p.nose)
+                    .total_cmp(&b.position.dist(&p.nose))
+            })
+            .map(|s| sidestep(p, s)),
+        Response::Ignore => None,
+    }
+}
+
+/// True if a scent is within a right angle either side of the way we are facing, as smelt from
+/// the nose
+fn ahead(p: &Perception, scent: &Scent) -> bool {
+    let turn = (p.nose.direction(&scent.position) - p.direction).abs();
+    turn.min(360.0 - turn) < 90.0
+}
+
+/// Turn side on to a scent, away from whichever side it is on
+fn sidestep(p: &Perception, scent: &Scent) -> f32 {
+    let towards = p.nose.direction(&scent.position);
+    let mut turn = towards - p.direction;
+    if turn > 180.0 {
+        turn -= 360.0;
+    } else if turn < -180.0 {
+        turn += 360.0;
+    }
+    let away = if turn > 0.0 {
+        towards - 90.0
+    } else {
+        towards + 90.0
+    };
+    (away + 360.0) % 360.0
+}
+
+/// The trail to use for a kind of pheromone, empty if the colony doesn't follow it
+fn trail<'s>(
+    p: &Perception,
+    scents: &'s Vec<Scent>,
+    none: &'s Vec<Scent>,
+    kind: PheromoneKind,
+) -> &'s Vec<Scent> {
+    if p.config.pheromone(kind).response == Response::Follow {
+        scents
+    } else {
+        none
+    }
+}
+
+/// Which way to go looking for food, anything in sight comes first, then keeping clear of food
+/// which has run out, then the food trail and, off the trail, keeping clear of searched ground
+fn forage(p: &Perception) -> f32 {
+    if let Some(f) = p
+        .food
+        .get_nearest(&p.position, ANT_DETECTION_RANGE, p.speed, p.direction)
+    {
+        return p.position.direction(&f);
+    }
+    if let Some(d) = respond(p, p.repellent_scents, PheromoneKind::Repellent) {
+        return d;
+    }
+    let none = vec![];
+    let trail = trail(p, p.food_scents, &none, PheromoneKind::Food);
+    if pheromone::nearest(trail, &p.nose, ANT_DETECTION_RANGE).is_none() {
+        if let Some(d) = respond(p, p.exploration_scents, PheromoneKind::Exploration) {
+            return d;
+        }
+    }
+    trail.get_avg_direction(&p.nose, ANT_DETECTION_RANGE, p.speed, p.direction)
+}
+
+/// Reverse a direction, i.e. turn around
+pub fn reverse(direction: f32) -> f32 {
+    match direction {
+        d if d >= 180.0 => d - 180.0,
+        d => d + 180.0,
+    }
+}
+
+/// The original forager, lays home scent while searching and food scent while returning
+pub struct DefaultBrain;
+
+impl AntBrain for DefaultBrain {
+    fn name(&self) -> &str {
+        "default"
+    }
+
+    fn decide(&self, p: &Perception) -> Vec<Action> {
+        if p.carrying_corpse {
+            if p.at_midden {
+                return vec![Action::DropCorpse, Action::Turn(reverse(p.direction))];
+            }
+            // ants always know the way to the midden, so head straight there
+            return vec![Action::Turn(p.position.direction(p.midden)), Action::Move];
+        }
+        // stand and fight, unless we are busy bringing food home
+        if !p.has_food {
+            if p.enemy_in_reach {
+                return vec![Action::Deposit(PheromoneKind::Alarm), Action::Attack];
+            }
+            if let Some(enemy) = p.enemy.as_ref() {
+                return vec![
+                    Action::Turn(p.position.direction(enemy)),
+                    Action::Deposit(PheromoneKind::Alarm),
+                    Action::Move,
+                ];
+            }
+        }
+        if p.has_food {
+            if p.at_home {
+                return vec![Action::Drop, Action::Turn(reverse(p.direction))];
+            }
+            let none = vec![];
+            let trail = trail(p, p.home_scents, &none, PheromoneKind::Home);
+            let direction = steer(p, Some(p.home), trail);
+            vec![
+                Action::Deposit(PheromoneKind::Food),
+                Action::Turn(direction),
+                Action::Move,
+            ]
+        } else {
+            if p.reachable_food.is_some() {
+                let mut actions = vec![Action::Deposit(PheromoneKind::Home)];
+                // taking the last of the food in sight, warn the others off
+                let left = p
+                    .food
+                    .iter()
+                    .filter(|f| f.dist(&p.position) < ANT_DETECTION_RANGE)
+                    .count();
+                if left <= 1 {
+                    actions.push(Action::Deposit(PheromoneKind::Repellent));
+                }
+                actions.push(Action::PickUp);
+                actions.push(Action::Turn(reverse(p.direction)));
+                return actions;
+            }
+            if p.reachable_corpse {
+                return vec![Action::PickUpCorpse];
+            }
+            // answer a call for help first, then the smell of a corpse, then look for food
+            let necromone = || {
+                p.necromone
+                    .get_nearest(&p.nose, ANT_DETECTION_RANGE, p.speed, p.direction)
+                    .map(|n| p.nose.direction(&n))
+            };
+            let direction = respond(p, p.alarm_scents, PheromoneKind::Alarm)
+                .or_else(necromone)
+                .unwrap_or_else(|| forage(p));
+            let mut actions = vec![Action::Deposit(PheromoneKind::Home)];
+            // only worth marking searched ground if the others take any notice of it
+            if p.config.exploration_pheromone.response != Response::Ignore
+                && pheromone::nearest(p.food_scents, &p.nose, ANT_DETECTION_RANGE).is_none()
+            {
+                actions.push(Action::Deposit(PheromoneKind::Exploration));
+            }
+            actions.push(Action::Turn(direction));
+            actions.push(Action::Move);
+            actions
+        }
+    }
+}
+
+#[test]
+fn default_brain_drops_food_at_home() {
+    let home = Coordinate::new(0.0, 0.0);
+    let midden = Coordinate::new(100.0, 0.0);
+    let food = vec![];
+    let scents = vec![];
+    let config = Config::default();
+    let perception = Perception {
+        position: Coordinate::new(1.0, 1.0),
+        nose: Coordinate::new(1.0, 1.0),
+        direction: 90.0,
+        speed: 1.0,
+        has_food: true,
+        food_kind: FoodKind::Sugar,
+        caste: Caste::Worker,
+        at_home: true,
+        reachable_food: None,
+        carrying_corpse: false,
+        at_midden: false,
+        reachable_corpse: false,
+        enemy: None,
+        enemy_in_reach: false,
+        home: &home,
+        midden: &midden,
+        food: &food,
+        home_scents: &scents,
+        food_scents: &scents,
+        alarm_scents: &scents,
+        repellent_scents: &scents,
+        exploration_scents: &scents,
+        necromone: &scents,
+        config: &config,
+    };
+
+    let actions = DefaultBrain.decide(&perception);
+    assert_eq!(actions, vec![Action::Drop, Action::Turn(270.0)]);
+
+    // a step short of home, this hop takes it in but the food is only dropped next tick
+    let arriving = Perception {
+        position: Coordinate::new(crate::world::HOME_SIZE + 0.5, 0.0),
+        nose: Coordinate::new(crate::world::HOME_SIZE + 0.5, 0.0),
+        direction: 180.0,
+        at_home: false,
+        ..perception
+    };
+    let actions = DefaultBrain.decide(&arriving);
+    assert!(actions.contains(&Action::Move));
+    assert!(!actions.contains(&Action::Drop));
+}
+
+#[test]
+fn default_brain_forages_fights_and_tidies() {
+    let home = Coordinate::new(0.0, 0.0);
+    let midden = Coordinate::new(100.0, 0.0);
+    // one piece of food in sight, to the east
+    let food = vec![Coordinate::new(110.0, 100.0)];
+    let scents = vec![];
+    let config = Config::default();
+    let at = |x: f32, y: f32| Perception {
+        position: Coordinate::new(x, y),
+        nose: Coordinate::new(x, y),
+        direction: 90.0,
+        speed: 1.0,
+        has_food: false,
+        food_kind: FoodKind::Sugar,
+        caste: Caste::Worker,
+        at_home: false,
+        reachable_food: None,
+        carrying_corpse: false,
+        at_midden: false,
+        reachable_corpse: false,
+        enemy: None,
+        enemy_in_reach: false,
+        home: &home,
+        midden: &midden,
+        food: &food,
+        home_scents: &scents,
+        food_scents: &scents,
+        alarm_scents: &scents,
+        repellent_scents: &scents,
+        exploration_scents: &scents,
+        necromone: &scents,
+        config: &config,
+    };
+    let home_scent = Action::Deposit(PheromoneKind::Home);
+
+    // head for food in sight
+    assert_eq!(
+        DefaultBrain.decide(&at(100.0, 100.0)),
+        vec![home_scent.clone(), Action::Turn(0.0), Action::Move]
+    );
+
+    // taking the last piece in sight warns the others off, unless there is more left
+    let reaching = Perception {
+        reachable_food: Some(0),
+        ..at(109.5, 100.0)
+    };
+    assert_eq!(
+        DefaultBrain.decide(&reaching),
+        vec![
+            home_scent.clone(),
+            Action::Deposit(PheromoneKind::Repellent),
+            Action::PickUp,
+            Action::Turn(270.0)
+        ]
+    );
+    let more = vec![food[0].clone(), Coordinate::new(112.0, 100.0)];
+    let plenty = Perception {
+        food: &more,
+        ..reaching
+    };
+    assert_eq!(
+        DefaultBrain.decide(&plenty),
+        vec![home_scent, Action::PickUp, Action::Turn(270.0)]
+    );
+
+    // corpses are picked up, taken straight to the midden and left there
+    let corpse = Perception {
+        reachable_corpse: true,
+        ..at(50.0, 50.0)
+    };
+    assert_eq!(DefaultBrain.decide(&corpse), vec![Action::PickUpCorpse]);
+    let carrying = Perception {
+        carrying_corpse: true,
+        ..at(100.0, 50.0)
+    };
+    assert_eq!(
+        DefaultBrain.decide(&carrying),
+        vec![Action::Turn(270.0), Action::Move]
+    );
+    let arrived = Perception {
+        carrying_corpse: true,
+        at_midden: true,
+        ..at(100.0, 1.0)
+    };
+    assert_eq!(
+        DefaultBrain.decide(&arrived),
+        vec![Action::DropCorpse, Action::Turn(270.0)]
+    );
+
+    // bite an enemy in reach, go after one in sight, but not while bringing food home
+    let alarm = Action::Deposit(PheromoneKind::Alarm);
+    let biting = Perception {
+        enemy: Some(Coordinate::new(51.0, 50.0)),
+        enemy_in_reach: true,
+        ..at(50.0, 50.0)
+    };
+    assert_eq!(
+        DefaultBrain.decide(&biting),
+        vec![alarm.clone(), Action::Attack]
+    );
+    let chasing = Perception {
+        enemy: Some(Coordinate::new(60.0, 50.0)),
+        ..at(50.0, 50.0)
+    };
+    assert_eq!(
+        DefaultBrain.decide(&chasing),
+        vec![Action::Turn(0.0), alarm, Action::Move]
+    );
+    let laden = Perception {
+        has_food: true,
+        enemy: Some(Coordinate::new(51.0, 50.0)),
+        enemy_in_reach: true,
+        ..at(50.0, 50.0)
+    };
+    assert!(!DefaultBrain.decide(&laden).contains(&Action::Attack));
+}
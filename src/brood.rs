@@ -0,0 +1,101 @@
+use crate::coordinate::Coordinate;
+
+/// Ticks between each feeding of a larva
+pub const LARVA_FEED_INTERVAL: u32 = 50;
+
+/// The mother of the colony, without her no more eggs are laid
+#[derive(Debug, Clone, PartialEq)]
+pub struct Queen {
+    pub id: u64,
+    pub position: Coordinate,
+    pub life: u32,
+    /// ticks since the last egg was laid
+    pub since_laid: u32,
+}
+
+impl Queen {
+    pub fn new(id: u64, position: Coordinate, life: u32) -> Queen {
+        Queen {
+            id,
+            position,
+            life,
+            since_laid: 0,
+        }
+    }
+}
+
+/// How far along a member of the brood is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Egg,
+    /// needs feeding protein before it can pupate
+    Larva,
+    Pupa,
+}
+
+/// A young ant being raised in the nest
+#[derive(Debug, Clone, PartialEq)]
+pub struct Brood {
+    pub stage: Stage,
+    /// ticks spent in the current stage
+    pub age: u32,
+    /// protein eaten as a larva
+    pub fed: u32,
+}
+
+impl Brood {
+    pub fn new() -> Brood {
+        Brood {
+            stage: Stage::Egg,
+            age: 0,
+            fed: 0,
+        }
+    }
+
+    /// Move on to the next stage, starting the clock again
+    pub fn advance(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.age = 0;
+    }
+}
+
+impl Default for Brood {
+    fn default() -> Brood {
+        Brood::new()
+    }
+}
+
+#[test]
+fn eggs_take_time_to_become_ants() {
+    use crate::config::Config;
+    use crate::world::World;
+
+    let mut world = World::builder()
+        .seed(1)
+        .config(Config {
+            starting_ants: 0,
+            cluster_spawn_chance: 0.0,
+            ..Config::default()
+        })
+        .empty()
+        .build();
+    let c = world.config.clone();
+    let first_adult = c.egg_interval + c.egg_time + c.larva_time + c.pupa_time - 1;
+    for _ in 1..first_adult {
+        world.step();
+    }
+    assert!(world.ants.is_empty());
+    assert_eq!(
+        world.colonies[0].brood.len() as u32,
+        c.starting_sugar / c.egg_sugar
+    );
+
+    world.step();
+    assert_eq!(world.ants.len(), 1);
+
+    // the queen dies of old age like any other ant
+    world.colonies[0].queen.as_mut().unwrap().life = 1;
+    world.step();
+    assert!(world.colonies[0].queen.is_none());
+    assert_eq!(world.metrics.ants_died, 1);
+}
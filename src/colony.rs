@@ -0,0 +1,130 @@
+use std::rc::Rc;
+
+use crate::brain::{AntBrain, DefaultBrain};
+use crate::brood::{Brood, Queen};
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::food::{FoodKind, PerFood};
+use crate::pheromone::PheromoneKind;
+use crate::scent::Scent;
+use crate::world::{HOME_SIZE, X_SIZE, Y_SIZE};
+
+/// A nest and everything which belongs to it, its ants live in the world and point back here
+#[derive(Clone)]
+pub struct Colony {
+    /// index into the world's colonies
+    pub id: usize,
+    pub home_position: Coordinate,
+    /// where the colony leaves its dead, away from the nest
+    pub midden_position: Coordinate,
+    pub home_scents: Vec<Scent>,
+    /// a separate trail for each kind of food
+    pub food_scents: PerFood<Vec<Scent>>,
+    /// laid by ants in a fight, calls nestmates over to help
+    pub alarm_scents: Vec<Scent>,
+    /// left where food has run out
+    pub repellent_scents: Vec<Scent>,
+    /// left by ants searching away from the trails
+    pub exploration_scents: Vec<Scent>,
+    /// food stored at home
+    pub stores: PerFood<u32>,
+    pub queen: Option<Queen>,
+    /// eggs, larvae and pupae being raised at home
+    pub brood: Vec<Brood>,
+    /// brain given to newly spawned ants
+    pub brain: Rc<dyn AntBrain>,
+}
+
+impl Colony {
+    /// # Arguments
+    /// - `facing` direction from the nest to put the midden in
+    pub fn new(id: usize, home: Coordinate, facing: f32, queen: u64, config: &Config) -> Colony {
+        Colony {
+            id,
+            midden_position: home
+                .traverse_direction(facing, config.midden_distance)
+                .check_bounds(0.0, X_SIZE, 0.0, Y_SIZE),
+            queen: Some(Queen::new(queen, home.clone(), config.queen_life)),
+            home_position: home,
+            home_scents: vec![],
+            food_scents: PerFood::default(),
+            alarm_scents: vec![],
+            repellent_scents: vec![],
+            exploration_scents: vec![],
+            stores: config.starting_food(),
+            brood: vec![],
+            brain: Rc::new(DefaultBrain),
+        }
+    }
+
+    /// The scents of the given kind laid by the colony, `food` picks which food trail is wanted
+    pub fn scents(&self, kind: PheromoneKind, food: FoodKind) -> &Vec<Scent> {
+        match kind {
+            PheromoneKind::Home => &self.home_scents,
+            PheromoneKind::Food => &self.food_scents[food],
+            PheromoneKind::Alarm => &self.alarm_scents,
+            PheromoneKind::Repellent => &self.repellent_scents,
+            PheromoneKind::Exploration => &self.exploration_scents,
+        }
+    }
+
+    pub fn scents_mut(&mut self, kind: PheromoneKind, food: FoodKind) -> &mut Vec<Scent> {
+        match kind {
+            PheromoneKind::Home => &mut self.home_scents,
+            PheromoneKind::Food => &mut self.food_scents[food],
+            PheromoneKind::Alarm => &mut self.alarm_scents,
+            PheromoneKind::Repellent => &mut self.repellent_scents,
+            PheromoneKind::Exploration => &mut self.exploration_scents,
+        }
+    }
+
+    /// Every set of scents the colony keeps, a food trail for each kind of food and one of
+    /// everything else, always in the same order
+    pub fn trails(&self) -> Vec<(PheromoneKind, &Vec<Scent>)> {
+        vec![
+            (PheromoneKind::Home, &self.home_scents),
+            (PheromoneKind::Food, &self.food_scents.sugar),
+            (PheromoneKind::Food, &self.food_scents.protein),
+            (PheromoneKind::Alarm, &self.alarm_scents),
+            (PheromoneKind::Repellent, &self.repellent_scents),
+            (PheromoneKind::Exploration, &self.exploration_scents),
+        ]
+    }
+
+    pub fn trails_mut(&mut self) -> Vec<(PheromoneKind, &mut Vec<Scent>)> {
+        vec![
+            (PheromoneKind::Home, &mut self.home_scents),
+            (PheromoneKind::Food, &mut self.food_scents.sugar),
+            (PheromoneKind::Food, &mut self.food_scents.protein),
+            (PheromoneKind::Alarm, &mut self.alarm_scents),
+            (PheromoneKind::Repellent, &mut self.repellent_scents),
+            (PheromoneKind::Exploration, &mut self.exploration_scents),
+        ]
+    }
+
+    /// True if `pos` is close enough to home to drop food
+    pub fn at_home(&self, pos: &Coordinate) -> bool {
+        pos.dist(&self.home_position) < HOME_SIZE
+    }
+
+    /// True if the queen is alive and there is the sugar for her to lay an egg
+    pub fn can_lay(&self, config: &Config) -> bool {
+        self.queen.is_some() && self.stores.sugar >= config.egg_sugar
+    }
+}
+
+/// Where to put each nest along with the direction facing away from the middle of the map
+///
+/// A lone colony sits in the middle, otherwise they are spread evenly round a circle
+pub fn nest_sites(count: u32, spacing: f32) -> Vec<(Coordinate, f32)> {
+    let centre = Coordinate::new(X_SIZE / 2.0, Y_SIZE / 2.0);
+    if count <= 1 {
+        return vec![(centre, 0.0)];
+    }
+    (0..count)
+        .map(|i| {
+            let facing = i as f32 * 360.0 / count as f32;
+            (centre.traverse_direction(facing, spacing), facing)
+        })
+        .collect()
+}
@@ -0,0 +1,169 @@
+use crate::ant::Ant;
+use crate::coordinate::Coordinate;
+use crate::world::{World, X_SIZE, Y_SIZE};
+
+/// How close an enemy has to be to bite it
+pub const FIGHT_RANGE: f32 = 3.0;
+/// How many scents an ant is worth when working out who holds a bit of the map
+const TERRITORY_ANT_WEIGHT: u32 = 10;
+
+/// What an ant was raised to do, decides how well it fights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Caste {
+    Worker,
+    Soldier,
+}
+
+impl Caste {
+    /// Damage done with each bite
+    pub fn strength(&self) -> f32 {
+        match self {
+            Caste::Worker => 1.0,
+            Caste::Soldier => 3.0,
+        }
+    }
+
+    pub fn max_health(&self) -> f32 {
+        match self {
+            Caste::Worker => 10.0,
+            Caste::Soldier => 30.0,
+        }
+    }
+}
+
+/// Where an ant was at the start of a tick, so enemies can be spotted while ants are moving
+pub struct Sighting {
+    pub ant: u64,
+    pub colony: usize,
+    pub position: Coordinate,
+}
+
+impl Sighting {
+    pub fn of(ant: &Ant) -> Sighting {
+        Sighting {
+            ant: ant.id,
+            colony: ant.colony,
+            position: ant.position.clone(),
+        }
+    }
+}
+
+/// The closest ant from another colony within `range` of `ant`
+pub fn nearest_enemy<'s>(
+    sightings: &'s Vec<Sighting>,
+    ant: &Ant,
+    range: f32,
+) -> Option<&'s Sighting> {
+    sightings
+        .iter()
+        .filter(|s| s.colony != ant.colony && s.position.dist(&ant.position) < range)
+        .min_by(|a, b| {
+            a.position
+                .dist(&ant.position)
+                .total_cmp(&b.position.dist(&ant.position))
+        })
+}
+
+/// Which colony holds each cell of the map, going by where its ants and scents are
+pub struct Territory {
+    pub cell_size: f32,
+    pub width: usize,
+    pub height: usize,
+    /// row major, `None` for cells nobody has been to
+    pub cells: Vec<Option<usize>>,
+}
+
+impl Territory {
+    pub fn new(world: &World, cell_size: f32) -> Territory {
+        let width = (X_SIZE / cell_size).ceil() as usize;
+        let height = (Y_SIZE / cell_size).ceil() as usize;
+        let colonies = world.colonies.len();
+        let mut counts = vec![0u32; width * height * colonies];
+        let cell = |pos: &Coordinate| {
+            let x = ((pos.x / cell_size) as usize).min(width - 1);
+            let y = ((pos.y / cell_size) as usize).min(height - 1);
+            y * width + x
+        };
+
+        for c in world.colonies.iter() {
+            let scents = c
+                .home_scents
+                .iter()
+                .chain(c.food_scents.sugar.iter())
+                .chain(c.food_scents.protein.iter());
+            for s in scents {
+                counts[cell(&s.position) * colonies + c.id] += 1;
+            }
+        }
+        for a in world.ants.iter() {
+            counts[cell(&a.position) * colonies + a.colony] += TERRITORY_ANT_WEIGHT;
+        }
+
+        let cells = counts
+            .chunks(colonies.max(1))
+            .map(|c| {
+                // SAFE: there is at least one colony per cell
+                let (colony, count) = c.iter().enumerate().max_by_key(|(_, n)| **n).unwrap();
+                if *count > 0 {
+                    Some(colony)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Territory {
+            cell_size,
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn owner(&self, pos: &Coordinate) -> Option<usize> {
+        let x = ((pos.x / self.cell_size) as usize).min(self.width - 1);
+        let y = ((pos.y / self.cell_size) as usize).min(self.height - 1);
+        self.cells[y * self.width + x]
+    }
+
+    /// Fraction of the claimed cells held by each colony
+    pub fn shares(&self, colonies: usize) -> Vec<f32> {
+        let mut held = vec![0; colonies];
+        for c in self.cells.iter().flatten() {
+            held[*c] += 1;
+        }
+        let claimed = held.iter().sum::<u32>().max(1) as f32;
+        held.iter().map(|h| *h as f32 / claimed).collect()
+    }
+}
+
+#[test]
+fn rival_ants_fight_to_the_death() {
+    use crate::config::Config;
+
+    let mut world = World::builder()
+        .seed(1)
+        .config(Config {
+            colonies: 2,
+            starting_ants: 0,
+            starting_sugar: 0,
+            starting_protein: 0,
+            soldier_fraction: 0.0,
+            cluster_spawn_chance: 0.0,
+            ..Config::default()
+        })
+        .empty()
+        .build();
+    world.spawn_ant(0);
+    world.spawn_ant(1);
+    world.ants[0].position = Coordinate::new(200.0, 200.0);
+    world.ants[1].position = Coordinate::new(202.0, 200.0);
+    for _ in 0..50 {
+        world.step();
+    }
+
+    assert!(world.metrics.ants_killed > 0);
+    assert!(!world.corpses.is_empty());
+    assert!(
+        !world.colonies[0].alarm_scents.is_empty() || !world.colonies[1].alarm_scents.is_empty()
+    );
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+
+use crate::food::PerFood;
+use crate::pheromone::{Pheromone, PheromoneKind};
+use crate::wind::Wind;
+use crate::world::ANT_SPEED;
+
+/// Knobs for building and running a world
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// how many colonies share the map
+    pub colonies: u32,
+    /// how far each nest is from the middle of the map, when there is more than one
+    pub colony_spacing: f32,
+    /// chance of a new ant being raised as a soldier rather than a worker
+    pub soldier_fraction: f32,
+    /// how many clusters of food to scatter when the world is populated
+    pub food_clusters: u32,
+    /// how many of those clusters are protein, the rest are sugar
+    pub protein_clusters: u32,
+    /// how many pieces of food are in each cluster
+    pub food_cluster_size: u32,
+    /// food the colony starts with
+    pub starting_sugar: u32,
+    pub starting_protein: u32,
+    /// sugar the queen eats to lay an egg
+    pub egg_sugar: u32,
+    /// protein a larva has to be fed before it can pupate
+    pub larva_protein: u32,
+    /// workers the colony starts with
+    pub starting_ants: u32,
+    /// ticks an ant lives for
+    pub ant_life: u32,
+    /// ticks the queen lives for, the colony can't grow once she is gone
+    pub queen_life: u32,
+    /// fewest ticks between eggs, if there is the sugar to lay them
+    pub egg_interval: u32,
+    /// ticks spent as an egg, a larva and a pupa
+    pub egg_time: u32,
+    pub larva_time: u32,
+    pub pupa_time: u32,
+    /// chance each tick of a new cluster appearing somewhere at random
+    pub cluster_spawn_chance: f32,
+    /// ticks a patch lies empty before it grows back, 0 means it never does
+    pub regrowth_delay: u32,
+    /// ticks in a full cycle of seasons, 0 turns seasons off
+    pub season_length: u32,
+    /// how far abundance swings above and below normal over the seasons, 0 -> 1
+    pub season_amplitude: f32,
+    /// how far from home the colony keeps its midden
+    pub midden_distance: f32,
+    /// ticks before a corpse rots down into protein, 0 means it never does
+    pub corpse_decay: u32,
+    /// pieces of protein a rotted corpse becomes
+    pub corpse_food: u32,
+    /// how each pheromone fades and spreads, and what ants do about it
+    pub home_pheromone: Pheromone,
+    pub food_pheromone: Pheromone,
+    pub alarm_pheromone: Pheromone,
+    pub repellent_pheromone: Pheromone,
+    pub exploration_pheromone: Pheromone,
+    /// blows scents about, calm by default
+    pub wind: Wind,
+    /// size of each cell when ants move about a hex grid instead of roaming freely, 0 keeps
+    /// movement continuous, at most `ANT_SPEED` so ants can reach any food in their cell
+    pub hex_size: f32,
+    /// random hills to raise when no heightmap is given, 0 keeps the ground flat
+    pub hills: u32,
+    /// height of the highest ground, in the same units as the map
+    pub elevation: f32,
+    /// ticks of life an ant spends for every unit it climbs
+    pub climb_cost: f32,
+    /// how quickly scents run downhill into the hollows
+    pub scent_pooling: f32,
+    /// ponds scattered about when the world is populated
+    pub ponds: u32,
+    pub pond_radius: f32,
+    /// nestmates which have to be gathered at the water's edge, counting the ant going in, for
+    /// it to hold a bridge rather than drown
+    pub bridge_ants: u32,
+    /// ticks an ant holds a bridge for before carrying on
+    pub bridge_time: u32,
+    /// check the world after every tick and stop it at the first thing found wrong, which is
+    /// kept as `World::violation`, see `invariants::check`, slow so only for tracking down bugs
+    pub check_invariants: bool,
+}
+
+impl Config {
+    pub fn starting_food(&self) -> PerFood<u32> {
+        PerFood {
+            sugar: self.starting_sugar,
+            protein: self.starting_protein,
+        }
+    }
+
+    pub fn pheromone(&self, kind: PheromoneKind) -> &Pheromone {
+        match kind {
+            PheromoneKind::Home => &self.home_pheromone,
+            PheromoneKind::Food => &self.food_pheromone,
+            PheromoneKind::Alarm => &self.alarm_pheromone,
+            PheromoneKind::Repellent => &self.repellent_pheromone,
+            PheromoneKind::Exploration => &self.exploration_pheromone,
+        }
+    }
+
+    /// Food it takes to raise a single ant from an egg
+    pub fn brood_cost(&self) -> PerFood<u32> {
+        PerFood {
+            sugar: self.egg_sugar,
+            protein: self.larva_protein,
+        }
+    }
+
+    /// Check the simulation can run with these settings, returns what is wrong with the first
+    /// field which doesn't make sense
+    pub fn validate(&self) -> Result<(), String> {
+        let at_least_one = [
+            ("colonies", self.colonies),
+            ("ant_life", self.ant_life),
+            ("queen_life", self.queen_life),
+            ("bridge_ants", self.bridge_ants),
+        ];
+        for (name, value) in at_least_one.iter() {
+            if *value == 0 {
+                return Err(format!("{} must be at least 1", name));
+            }
+        }
+        let fractions = [
+            ("soldier_fraction", self.soldier_fraction),
+            ("cluster_spawn_chance", self.cluster_spawn_chance),
+            ("season_amplitude", self.season_amplitude),
+            ("wind.gustiness", self.wind.gustiness),
+        ];
+        for (name, value) in fractions.iter() {
+            if !(0.0..=1.0).contains(value) {
+                return Err(format!("{} must be from 0 to 1, not {}", name, value));
+            }
+        }
+        let mut distances = vec![
+            ("colony_spacing", self.colony_spacing),
+            ("midden_distance", self.midden_distance),
+            ("hex_size", self.hex_size),
+            ("elevation", self.elevation),
+            ("climb_cost", self.climb_cost),
+            ("scent_pooling", self.scent_pooling),
+            ("pond_radius", self.pond_radius),
+            ("wind.speed", self.wind.speed),
+            ("wind.swirl", self.wind.swirl),
+            ("wind.sensing_bias", self.wind.sensing_bias),
+        ];
+        for kind in PheromoneKind::ALL.iter() {
+            distances.push(("pheromone diffusion", self.pheromone(*kind).diffusion));
+        }
+        for (name, value) in distances.iter() {
+            if !value.is_finite() || *value < 0.0 {
+                return Err(format!("{} must be 0 or more, not {}", name, value));
+            }
+        }
+        if !self.wind.direction.is_finite() {
+            return Err("wind.direction must be a number".to_string());
+        }
+        if self.hex_size > ANT_SPEED {
+            return Err(format!(
+                "hex_size can be at most {} so ants can reach any food in their cell, not {}",
+                ANT_SPEED, self.hex_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            colonies: 1,
+            colony_spacing: 150.0,
+            soldier_fraction: 0.2,
+            food_clusters: 15,
+            protein_clusters: 5,
+            food_cluster_size: 150,
+            starting_sugar: 60,
+            starting_protein: 40,
+            egg_sugar: 3,
+            larva_protein: 2,
+            starting_ants: 10,
+            ant_life: 4000,
+            queen_life: 50000,
+            egg_interval: 20,
+            egg_time: 100,
+            larva_time: 200,
+            pupa_time: 100,
+            cluster_spawn_chance: 0.001,
+            regrowth_delay: 2000,
+            season_length: 12000,
+            season_amplitude: 0.5,
+            midden_distance: 100.0,
+            corpse_decay: 3000,
+            corpse_food: 3,
+            home_pheromone: Pheromone::home(),
+            food_pheromone: Pheromone::food(),
+            alarm_pheromone: Pheromone::alarm(),
+            repellent_pheromone: Pheromone::repellent(),
+            exploration_pheromone: Pheromone::exploration(),
+            wind: Wind::calm(),
+            hex_size: 0.0,
+            hills: 0,
+            elevation: 50.0,
+            climb_cost: 2.0,
+            scent_pooling: 0.5,
+            ponds: 0,
+            pond_radius: 40.0,
+            bridge_ants: 5,
+            bridge_time: 300,
+            check_invariants: false,
+        }
+    }
+}
+
+#[test]
+fn nonsense_configs_are_rejected() {
+    assert_eq!(Config::default().validate(), Ok(()));
+    let broken = [
+        Config {
+            ant_life: 0,
+            ..Config::default()
+        },
+        Config {
+            colonies: 0,
+            ..Config::default()
+        },
+        Config {
+            hex_size: ANT_SPEED * 2.0,
+            ..Config::default()
+        },
+        Config {
+            soldier_fraction: f32::NAN,
+            ..Config::default()
+        },
+    ];
+    for config in broken.iter() {
+        assert!(config.validate().is_err(), "{:?} passed", config);
+    }
+
+    let scenario = crate::scenario::Scenario::parse(
+        r#"
+        name = "short lived"
+        [config]
+        ant_life = 0
+        "#,
+    );
+    assert!(scenario.is_err());
+}
@@ -1,7 +1,5 @@
-#![feature(drain_filter)]
-#![feature(destructuring_assignment)]
-
 use crate::navigable::Navigable;
+use crate::rng;
 use rand::prelude::*;
 use rand_distr::Normal;
 
@@ -84,6 +82,7 @@ impl Coordinate {
     }
 }
 
+#[cfg(feature = "viewer")]
 impl Into<ggez::mint::Point2<f32>> for Coordinate {
     fn into(self) -> ggez::mint::Point2<f32> {
         ggez::mint::Point2 {
@@ -121,7 +120,7 @@ impl Navigable for Coordinate {
         } else {
             // we were unable to find an average position, so we need to make one up
             let distribution = Normal::new(dir, RANDOMNESS).unwrap();
-            let mut direction = distribution.sample(&mut rand::thread_rng());
+            let mut direction = rng::with(|r| distribution.sample(r));
             // match to a valid direction
             while direction > 359.9 {
                 direction -= 359.9;
@@ -158,7 +157,7 @@ impl Navigable for Vec<Coordinate> {
             /*
             // we were unable to find a position, so we need to make one up
             let distribution = Normal::new(dir, RANDOMNESS).unwrap();
-            let mut direction = distribution.sample(&mut rand::thread_rng());
+            let mut direction = rng::with(|r| distribution.sample(r));
             // match to a valid direction
             while direction > 359.9 {
                 direction -= 359.9;
@@ -192,7 +191,7 @@ impl Navigable for Vec<Coordinate> {
         if count == 0 {
             // we have no in-range points
             let distribution = Normal::new(dir, RANDOMNESS).unwrap();
-            let mut direction = distribution.sample(&mut rand::thread_rng());
+            let mut direction = rng::with(|r| distribution.sample(r));
             while direction > 359.9 {
                 direction -= 359.9;
             }
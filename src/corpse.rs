@@ -0,0 +1,103 @@
+use rand::prelude::*;
+
+use crate::brain;
+use crate::coordinate::Coordinate;
+use crate::rng;
+use crate::scent::Scent;
+use crate::world::{ANT_SPEED, HOME_SIZE, X_SIZE, Y_SIZE};
+
+/// Ticks between each puff of necromone given off by a corpse
+pub const NECROMONE_INTERVAL: u32 = 10;
+/// How long necromone lingers for, much shorter than a trail
+pub const NECROMONE_LIFE: u32 = 120;
+/// How far from a corpse its necromone drifts
+pub const NECROMONE_SPREAD: f32 = 30.0;
+
+/// The body of a dead ant, waiting to be carried off to the midden
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corpse {
+    pub position: Coordinate,
+    /// ticks since the ant died
+    pub age: u32,
+}
+
+impl Corpse {
+    pub fn new(position: Coordinate) -> Corpse {
+        Corpse { position, age: 0 }
+    }
+
+    /// Give off a puff of necromone around the corpse, each scent points back towards it
+    pub fn emit_necromone(&self) -> Scent {
+        let (angle, dist) =
+            rng::with(|r| (r.gen::<f32>() * 359.9, r.gen::<f32>() * NECROMONE_SPREAD));
+        Scent {
+            position: self
+                .position
+                .traverse_direction(angle, dist)
+                .check_bounds(0.0, X_SIZE, 0.0, Y_SIZE),
+            direction: brain::reverse(angle),
+            life: NECROMONE_LIFE,
+        }
+    }
+}
+
+/// True if `pos` is inside the midden, where corpses are left
+pub fn in_midden(pos: &Coordinate, midden: &Coordinate) -> bool {
+    pos.dist(midden) < HOME_SIZE
+}
+
+pub fn in_any_midden(pos: &Coordinate, middens: &Vec<Coordinate>) -> bool {
+    middens.iter().any(|m| in_midden(pos, m))
+}
+
+/// Find the closest corpse the ant can reach in its next "hop", ignoring those already in a
+/// midden
+pub fn reachable_corpse(
+    pos: &Coordinate,
+    corpses: &Vec<Corpse>,
+    middens: &Vec<Coordinate>,
+) -> Option<usize> {
+    corpses
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| pos.dist(&c.position) < ANT_SPEED && !in_any_midden(&c.position, middens))
+        .min_by(|(_, a), (_, b)| pos.dist(&a.position).total_cmp(&pos.dist(&b.position)))
+        .map(|(i, _)| i)
+}
+
+#[test]
+fn corpses_are_carried_to_the_midden() {
+    use crate::config::Config;
+    use crate::world::World;
+
+    let mut world = World::builder()
+        .seed(1)
+        .config(Config {
+            starting_sugar: 0,
+            cluster_spawn_chance: 0.0,
+            ..Config::default()
+        })
+        .empty()
+        .build();
+    world.spawn_ant(0);
+    world
+        .corpses
+        .push(Corpse::new(world.ants[0].position.clone()));
+    for _ in 0..200 {
+        world.step();
+    }
+
+    assert_eq!(world.metrics.corpses_buried, 1);
+    assert!(in_midden(
+        &world.corpses[0].position,
+        &world.colonies[0].midden_position
+    ));
+
+    // one carried off the edge of the map is left on the edge rather than vanishing with the ant
+    world.ants[0].corpse = Some(world.corpses.remove(0));
+    world.ants[0].position = Coordinate::new(X_SIZE + 10.0, 50.0);
+    world.step();
+    assert!(world.ants.is_empty());
+    assert_eq!(world.corpses.len(), 1);
+    assert_eq!(world.corpses[0].position.x, X_SIZE);
+}
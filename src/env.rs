@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ant::{Ant, ANT_DETECTION_RANGE};
+use crate::brain::Action;
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::navigable::Navigable;
+use crate::scent::{Scent, SCENT_LIFE};
+use crate::world::World;
+
+/// Number of slices the area around an ant is split into when sampling scents
+pub const SCENT_SECTORS: usize = 8;
+/// Length of the vector produced by `Observation::encode`
+pub const OBSERVATION_SIZE: usize = SCENT_SECTORS * 2 + 6;
+
+const PICKUP_REWARD: f32 = 0.1;
+const DELIVERY_REWARD: f32 = 1.0;
+
+/// What a single ant can sense, all directions are relative to the ant's heading
+///
+/// Sectors start dead ahead and go round in the direction of increasing angle, vectors are
+/// `(ahead, side)` so `(1.0, 0.0)` is straight in front of the ant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub home_scents: [f32; SCENT_SECTORS],
+    /// scents on the trail for the kind of food the ant is after
+    pub food_scents: [f32; SCENT_SECTORS],
+    /// unit vector to the nearest food of the ant's kind in range, zero if there is none
+    pub food_direction: (f32, f32),
+    /// unit vector to home, ants always know which way home is
+    pub home_direction: (f32, f32),
+    pub carrying: bool,
+    /// true if the ant is after protein rather than sugar
+    pub protein: bool,
+}
+
+impl Observation {
+    pub fn new(world: &World, ant: &Ant) -> Observation {
+        let colony = &world.colonies[ant.colony];
+        let food_direction = match world.food_positions[ant.food_kind].get_nearest(
+            &ant.position,
+            ANT_DETECTION_RANGE,
+            ant.speed,
+            ant.direction,
+        ) {
+            Some(f) => unit_vector(relative_angle(ant, &f)),
+            None => (0.0, 0.0),
+        };
+        Observation {
+            home_scents: sample_scents(ant, &colony.home_scents),
+            food_scents: sample_scents(ant, &colony.food_scents[ant.food_kind]),
+            food_direction,
+            home_direction: unit_vector(relative_angle(ant, &colony.home_position)),
+            carrying: ant.has_food,
+            protein: ant.food_kind == FoodKind::Protein,
+        }
+    }
+
+    /// Flatten into a fixed size vector of `OBSERVATION_SIZE` values
+    ///
+    /// Layout is home scents, food scents, food direction, home direction, carrying flag,
+    /// protein flag
+    pub fn encode(&self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(OBSERVATION_SIZE);
+        out.extend_from_slice(&self.home_scents);
+        out.extend_from_slice(&self.food_scents);
+        out.push(self.food_direction.0);
+        out.push(self.food_direction.1);
+        out.push(self.home_direction.0);
+        out.push(self.home_direction.1);
+        out.push(if self.carrying { 1.0 } else { 0.0 });
+        out.push(if self.protein { 1.0 } else { 0.0 });
+        out
+    }
+}
+
+/// Angle of `target` as seen from the ant, 0 -> 359 where 0 is dead ahead
+fn relative_angle(ant: &Ant, target: &Coordinate) -> f32 {
+    if ant.position == *target {
+        // standing right on it, so call it ahead of us
+        return 0.0;
+    }
+    let mut angle = ant.position.direction(target) - ant.direction;
+    while angle < 0.0 {
+        angle += 360.0;
+    }
+    while angle >= 360.0 {
+        angle -= 360.0;
+    }
+    angle
+}
+
+fn unit_vector(angle: f32) -> (f32, f32) {
+    (angle.to_radians().cos(), angle.to_radians().sin())
+}
+
+/// Sum the strength of nearby scents into sectors around the ant
+fn sample_scents(ant: &Ant, scents: &Vec<Scent>) -> [f32; SCENT_SECTORS] {
+    let mut samples = [0.0; SCENT_SECTORS];
+    let sector_size = 360.0 / SCENT_SECTORS as f32;
+    for s in scents {
+        if ant.position.dist(&s.position) < ANT_DETECTION_RANGE {
+            let sector = (relative_angle(ant, &s.position) / sector_size) as usize;
+            let life = s.life.min(SCENT_LIFE);
+            samples[sector.min(SCENT_SECTORS - 1)] += life as f32 / SCENT_LIFE as f32;
+        }
+    }
+    samples
+}
+
+/// The outcome of a single `Env::step`, keyed by ant id
+pub struct StepResult {
+    /// observations for every ant alive after the step
+    pub observations: BTreeMap<u64, Observation>,
+    pub rewards: BTreeMap<u64, f32>,
+    /// true for ants which died during the step
+    pub dones: BTreeMap<u64, bool>,
+    /// true once the colony has died out and can not raise any more ants, or the episode has run
+    /// for `Env::max_ticks`
+    pub done: bool,
+}
+
+/// A gym style wrapper around the world, for training ant controllers
+///
+/// Each ant is an agent, ants which are not given actions in a step follow their brain
+pub struct Env {
+    pub world: World,
+    /// what each episode's world is built with
+    pub config: Config,
+    /// ticks before an episode is done, 0 means it runs until the colony dies out
+    pub max_ticks: u64,
+}
+
+impl Env {
+    pub fn new() -> Env {
+        Env::with_config(Config::default())
+    }
+
+    /// An environment whose episodes are built from `config`, `reset` panics if it isn't valid,
+    /// see `Config::validate`
+    pub fn with_config(config: Config) -> Env {
+        Env {
+            world: World::with_config(config.clone()),
+            config,
+            max_ticks: 0,
+        }
+    }
+
+    /// Start a fresh episode, the same seed will always give the same world
+    pub fn reset(&mut self, seed: u64) -> BTreeMap<u64, Observation> {
+        self.world = World::builder()
+            .seed(seed)
+            .config(self.config.clone())
+            .build();
+        self.observe()
+    }
+
+    pub fn observe(&self) -> BTreeMap<u64, Observation> {
+        self.world
+            .ants
+            .iter()
+            .map(|a| (a.id, Observation::new(&self.world, a)))
+            .collect()
+    }
+
+    /// Advance one tick
+    ///
+    /// # Arguments
+    /// - `actions` the actions each ant should take, keyed by ant id
+    pub fn step(&mut self, actions: &HashMap<u64, Vec<Action>>) -> StepResult {
+        let before: HashMap<u64, bool> =
+            self.world.ants.iter().map(|a| (a.id, a.has_food)).collect();
+
+        self.world.step_with(actions);
+
+        let after: HashMap<u64, bool> =
+            self.world.ants.iter().map(|a| (a.id, a.has_food)).collect();
+
+        let mut rewards = BTreeMap::new();
+        let mut dones = BTreeMap::new();
+        for (id, had_food) in before.iter() {
+            let reward = match after.get(id) {
+                Some(true) if !had_food => PICKUP_REWARD,
+                Some(false) if *had_food => DELIVERY_REWARD,
+                _ => 0.0,
+            };
+            rewards.insert(*id, reward);
+            dones.insert(*id, !after.contains_key(id));
+        }
+        // ants which have just been spawned
+        for id in after.keys().filter(|id| !before.contains_key(id)) {
+            rewards.insert(*id, 0.0);
+            dones.insert(*id, false);
+        }
+
+        StepResult {
+            observations: self.observe(),
+            rewards,
+            dones,
+            done: self.world.collapsed()
+                || (self.max_ticks > 0 && self.world.tick >= self.max_ticks),
+        }
+    }
+}
+
+impl Default for Env {
+    fn default() -> Env {
+        Env::new()
+    }
+}
+
+#[test]
+fn env_reset_is_repeatable() {
+    let run = |seed| {
+        let mut env = Env::new();
+        env.reset(seed);
+        let actions = HashMap::new();
+        for _ in 0..50 {
+            env.step(&actions);
+        }
+        env.observe()
+    };
+
+    let observations = run(7);
+    assert!(!observations.is_empty());
+    assert_eq!(observations, run(7));
+    for o in observations.values() {
+        assert_eq!(o.encode().len(), OBSERVATION_SIZE);
+    }
+}
+
+#[test]
+fn rewards_and_episode_end() {
+    let mut env = Env::with_config(Config {
+        starting_ants: 1,
+        ..Config::default()
+    });
+    env.max_ticks = 10;
+    env.reset(2);
+    assert_eq!(env.world.ants.len(), 1);
+    let id = env.world.ants[0].id;
+    let act = |action: Action| {
+        let mut actions = HashMap::new();
+        actions.insert(id, vec![action]);
+        actions
+    };
+
+    // food right under the ant
+    let (kind, position) = (
+        env.world.ants[0].food_kind,
+        env.world.ants[0].position.clone(),
+    );
+    env.world.food_positions[kind].push(position);
+    let result = env.step(&act(Action::PickUp));
+    assert_eq!(result.rewards[&id], PICKUP_REWARD);
+    assert!(!result.done);
+
+    env.world.ants[0].position = env.world.colonies[0].home_position.clone();
+    let result = env.step(&act(Action::Drop));
+    assert_eq!(result.rewards[&id], DELIVERY_REWARD);
+    assert!(!result.dones[&id]);
+
+    // nothing for doing nothing, and done once the ticks run out
+    while env.world.tick < 9 {
+        let result = env.step(&act(Action::Move));
+        assert_eq!(result.rewards[&id], 0.0);
+        assert!(!result.done);
+    }
+    assert!(env.step(&HashMap::new()).done);
+
+    // or as soon as the colony is gone
+    env.max_ticks = 0;
+    env.reset(2);
+    env.world.ants.clear();
+    env.world.colonies[0].queen = None;
+    env.world.colonies[0].brood.clear();
+    assert!(env.step(&HashMap::new()).done);
+}
@@ -0,0 +1,217 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use tracing::{debug, info, trace, warn};
+
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::pheromone::PheromoneKind;
+
+/// Something notable which happened inside the world during a tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    AntSpawned {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The ant's life ran out, leaving a corpse behind
+    AntDied {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The queen's life ran out, no more eggs will be laid
+    QueenDied {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The ant was bitten to death by an enemy
+    AntKilled {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The ant bit `target`, an ant from another colony
+    AntAttacked {
+        ant: u64,
+        position: Coordinate,
+        target: u64,
+    },
+    /// The ant wandered off the edge of the map
+    AntLost {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The ant walked into water with no nestmates around to help it across
+    AntDrowned {
+        ant: u64,
+        position: Coordinate,
+    },
+    FoodPickedUp {
+        ant: u64,
+        position: Coordinate,
+        kind: FoodKind,
+    },
+    FoodDelivered {
+        ant: u64,
+        position: Coordinate,
+        kind: FoodKind,
+    },
+    ScentDeposited {
+        ant: u64,
+        position: Coordinate,
+        kind: PheromoneKind,
+    },
+    CorpsePickedUp {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// A corpse was left in the midden
+    CorpseBuried {
+        ant: u64,
+        position: Coordinate,
+    },
+    /// The ant carrying a corpse died, drowned or wandered off, and put it down where it was
+    CorpseDropped {
+        ant: u64,
+        position: Coordinate,
+    },
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::AntSpawned { .. } => "AntSpawned",
+            Event::AntDied { .. } => "AntDied",
+            Event::QueenDied { .. } => "QueenDied",
+            Event::AntKilled { .. } => "AntKilled",
+            Event::AntAttacked { .. } => "AntAttacked",
+            Event::AntLost { .. } => "AntLost",
+            Event::AntDrowned { .. } => "AntDrowned",
+            Event::FoodPickedUp { .. } => "FoodPickedUp",
+            Event::FoodDelivered { .. } => "FoodDelivered",
+            Event::ScentDeposited { .. } => "ScentDeposited",
+            Event::CorpsePickedUp { .. } => "CorpsePickedUp",
+            Event::CorpseBuried { .. } => "CorpseBuried",
+            Event::CorpseDropped { .. } => "CorpseDropped",
+        }
+    }
+
+    pub fn ant(&self) -> u64 {
+        match self {
+            Event::AntSpawned { ant, .. }
+            | Event::AntDied { ant, .. }
+            | Event::QueenDied { ant, .. }
+            | Event::AntKilled { ant, .. }
+            | Event::AntAttacked { ant, .. }
+            | Event::AntLost { ant, .. }
+            | Event::AntDrowned { ant, .. }
+            | Event::FoodPickedUp { ant, .. }
+            | Event::FoodDelivered { ant, .. }
+            | Event::ScentDeposited { ant, .. }
+            | Event::CorpsePickedUp { ant, .. }
+            | Event::CorpseBuried { ant, .. }
+            | Event::CorpseDropped { ant, .. } => *ant,
+        }
+    }
+
+    pub fn position(&self) -> &Coordinate {
+        match self {
+            Event::AntSpawned { position, .. }
+            | Event::AntDied { position, .. }
+            | Event::QueenDied { position, .. }
+            | Event::AntKilled { position, .. }
+            | Event::AntAttacked { position, .. }
+            | Event::AntLost { position, .. }
+            | Event::AntDrowned { position, .. }
+            | Event::FoodPickedUp { position, .. }
+            | Event::FoodDelivered { position, .. }
+            | Event::ScentDeposited { position, .. }
+            | Event::CorpsePickedUp { position, .. }
+            | Event::CorpseBuried { position, .. }
+            | Event::CorpseDropped { position, .. } => position,
+        }
+    }
+}
+
+/// Something which wants to hear about events, given the tick they happened on
+pub type Subscriber = Box<dyn FnMut(u64, &Event)>;
+
+/// Hands events out to subscribers and, optionally, a log file
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscriber>,
+    log: Option<BufWriter<File>>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    pub fn subscribe<F>(&mut self, subscriber: F)
+    where
+        F: FnMut(u64, &Event) + 'static,
+    {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// Write every event to `path` as csv, one `tick,event,ant,x,y` row per event
+    pub fn log_to(&mut self, path: &Path) -> io::Result<()> {
+        let mut log = BufWriter::new(File::create(path)?);
+        writeln!(log, "tick,event,ant,x,y")?;
+        self.log = Some(log);
+        Ok(())
+    }
+
+    pub fn emit(&mut self, tick: u64, event: Event) {
+        let (name, ant, pos) = (event.name(), event.ant(), event.position());
+        // the everyday comings and goings are only worth seeing when tracing
+        match event {
+            Event::QueenDied { .. } => info!(tick, ant, x = pos.x, y = pos.y, "{}", name),
+            Event::ScentDeposited { .. }
+            | Event::FoodPickedUp { .. }
+            | Event::AntAttacked { .. } => {
+                trace!(tick, ant, x = pos.x, y = pos.y, "{}", name)
+            }
+            _ => debug!(tick, ant, x = pos.x, y = pos.y, "{}", name),
+        }
+        for s in self.subscribers.iter_mut() {
+            s(tick, &event);
+        }
+        if let Some(log) = self.log.as_mut() {
+            let pos = event.position();
+            let name = match &event {
+                Event::ScentDeposited { kind, .. } => format!("{}({:?})", event.name(), kind),
+                Event::FoodPickedUp { kind, .. } | Event::FoodDelivered { kind, .. } => {
+                    format!("{}({:?})", event.name(), kind)
+                }
+                e => e.name().to_owned(),
+            };
+            // a broken log shouldn't take down the simulation, so stop logging instead
+            if let Err(e) = writeln!(log, "{},{},{},{},{}", tick, name, event.ant(), pos.x, pos.y) {
+                warn!(
+                    "unable to write to the event log, no more events will be logged: {}",
+                    e
+                );
+                self.log = None;
+            }
+        }
+    }
+}
+
+#[test]
+fn subscribers_hear_about_spawns() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let seen = Rc::new(RefCell::new(vec![]));
+    let mut world = crate::world::World::new();
+    let s = seen.clone();
+    world
+        .events
+        .subscribe(move |tick, e| s.borrow_mut().push((tick, e.name())));
+    world.step();
+    world.spawn_ant(0);
+
+    assert!(seen.borrow().contains(&(1, "AntSpawned")));
+}
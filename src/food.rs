@@ -0,0 +1,118 @@
+use std::f32::consts::PI;
+use std::ops::{Index, IndexMut};
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::world::gen_food_cluster;
+
+/// The different kinds of food the colony needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FoodKind {
+    Sugar,
+    Protein,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 2] = [FoodKind::Sugar, FoodKind::Protein];
+}
+
+/// One of something for each kind of food, indexed by `FoodKind`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerFood<T> {
+    pub sugar: T,
+    pub protein: T,
+}
+
+impl<T> PerFood<T> {
+    pub fn new<F>(mut f: F) -> PerFood<T>
+    where
+        F: FnMut(FoodKind) -> T,
+    {
+        PerFood {
+            sugar: f(FoodKind::Sugar),
+            protein: f(FoodKind::Protein),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FoodKind, &T)> {
+        FoodKind::ALL.iter().map(move |k| (*k, &self[*k]))
+    }
+}
+
+impl<T> Index<FoodKind> for PerFood<T> {
+    type Output = T;
+
+    fn index(&self, kind: FoodKind) -> &T {
+        match kind {
+            FoodKind::Sugar => &self.sugar,
+            FoodKind::Protein => &self.protein,
+        }
+    }
+}
+
+impl<T> IndexMut<FoodKind> for PerFood<T> {
+    fn index_mut(&mut self, kind: FoodKind) -> &mut T {
+        match kind {
+            FoodKind::Sugar => &mut self.sugar,
+            FoodKind::Protein => &mut self.protein,
+        }
+    }
+}
+
+/// Somewhere a cluster of food was put down, which grows back once it has all been eaten
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    pub kind: FoodKind,
+    /// where the cluster is generated around
+    pub origin: Coordinate,
+    /// the middle of the food as it was last grown, and how far out it reaches
+    pub centre: Coordinate,
+    pub radius: f32,
+    /// the tick the patch was first seen with no food left
+    pub depleted_at: Option<u64>,
+}
+
+impl Patch {
+    pub fn new(kind: FoodKind, x: f32, y: f32) -> Patch {
+        Patch {
+            kind,
+            origin: Coordinate::new(x, y),
+            centre: Coordinate::new(x, y),
+            radius: 0.0,
+            depleted_at: None,
+        }
+    }
+
+    /// Generate a fresh cluster of `size` pieces of food around the origin
+    pub fn grow(&mut self, size: u32) -> Vec<Coordinate> {
+        let cluster = gen_food_cluster(size, self.origin.x, self.origin.y);
+        if !cluster.is_empty() {
+            let n = cluster.len() as f32;
+            self.centre = Coordinate::new(
+                cluster.iter().map(|f| f.x).sum::<f32>() / n,
+                cluster.iter().map(|f| f.y).sum::<f32>() / n,
+            );
+            self.radius = cluster
+                .iter()
+                .map(|f| f.dist(&self.centre))
+                .fold(0.0, f32::max)
+                + 1.0;
+        }
+        self.depleted_at = None;
+        cluster
+    }
+}
+
+/// How plentiful food is at `tick`, 1.0 is normal
+///
+/// Follows a sine wave over `season_length` ticks, swinging by `season_amplitude` either way
+pub fn abundance(config: &Config, tick: u64) -> f32 {
+    if config.season_length == 0 {
+        return 1.0;
+    }
+    let phase = (tick % config.season_length as u64) as f32 / config.season_length as f32;
+    (1.0 + config.season_amplitude * (2.0 * PI * phase).sin()).max(0.0)
+}
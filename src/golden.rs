@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::coordinate::Coordinate;
+use crate::pheromone::PheromoneKind;
+use crate::scenario::Scenario;
+use crate::world::World;
+
+/// Ticks each scenario is traced for
+pub const GOLDEN_TICKS: u64 = 1000;
+/// Ticks between lines of a trace
+pub const GOLDEN_INTERVAL: u64 = 50;
+/// Set to regenerate the golden files rather than check against them
+pub const REGENERATE: &str = "REGENERATE_GOLDENS";
+/// Positions and headings are hashed to this many parts in a unit, so the last few bits, which
+/// can differ between platforms' `sin`, `cos` and `atan2`, don't change the hash
+const HASH_PRECISION: f32 = 1000.0;
+
+/// FNV-1a, which unlike the std hasher is fixed so the hashes can be committed
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn float(&mut self, v: f32) {
+        self.write(&((v * HASH_PRECISION).round() as i32).to_le_bytes());
+    }
+
+    fn coordinate(&mut self, c: &Coordinate) {
+        self.float(c.x);
+        self.float(c.y);
+    }
+}
+
+/// One line summing up the world as it stands, counts are written out so a failure shows what
+/// changed, positions only go into the hash
+///
+/// Rounding hides differences in the last few bits, but over a long enough run those can still
+/// send an ant a different way, so goldens recorded on one platform aren't guaranteed to match on
+/// another
+pub fn line(world: &World) -> String {
+    let mut hash = Fnv::new();
+    for a in world.ants.iter() {
+        hash.write(&a.id.to_le_bytes());
+        hash.coordinate(&a.position);
+        hash.float(a.direction);
+        hash.write(&[a.has_food as u8]);
+    }
+    for food in [&world.food_positions.sugar, &world.food_positions.protein].iter() {
+        for f in food.iter() {
+            hash.coordinate(f);
+        }
+    }
+    let mut line = format!(
+        "tick {} ants {} sugar {} protein {} stored {}/{}",
+        world.tick,
+        world.ants.len(),
+        world.food_positions.sugar.len(),
+        world.food_positions.protein.len(),
+        world.colonies.iter().map(|c| c.stores.sugar).sum::<u32>(),
+        world.colonies.iter().map(|c| c.stores.protein).sum::<u32>(),
+    );
+    for kind in PheromoneKind::ALL.iter() {
+        let count: usize = world
+            .colonies
+            .iter()
+            .flat_map(|c| c.trails())
+            .filter(|(k, _)| k == kind)
+            .map(|(_, scents)| {
+                for s in scents.iter() {
+                    hash.coordinate(&s.position);
+                }
+                scents.len()
+            })
+            .sum();
+        line += &format!(" {} {}", kind.name(), count);
+    }
+    line + &format!(" hash {:016x}", hash.0)
+}
+
+/// Lines summing up a scenario every `GOLDEN_INTERVAL` ticks for `GOLDEN_TICKS` ticks
+pub fn trace(scenario: &Scenario) -> Vec<String> {
+    let mut world = scenario.build();
+    let mut lines = vec![line(&world)];
+    while world.tick < GOLDEN_TICKS {
+        world.step();
+        if world.tick.is_multiple_of(GOLDEN_INTERVAL) {
+            lines.push(line(&world));
+        }
+    }
+    lines
+}
+
+/// Where the golden trace for a scenario file is kept
+pub fn golden_path(dir: &Path, scenario: &Path) -> PathBuf {
+    let stem = scenario.file_stem().unwrap_or_default();
+    dir.join(stem).with_extension("golden")
+}
+
+/// Check a trace against what was recorded, describing the first line which differs
+pub fn compare(expected: &str, actual: &Vec<String>) -> Result<(), String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    for (i, line) in actual.iter().enumerate() {
+        match expected.get(i) {
+            Some(e) if e == line => continue,
+            Some(e) => return Err(format!("expected {}\n     got {}", e, line)),
+            None => return Err(format!("golden ends before {}", line)),
+        }
+    }
+    if expected.len() > actual.len() {
+        return Err(format!("trace ends before {}", expected[actual.len()]));
+    }
+    Ok(())
+}
+
+/// Trace every scenario in `scenarios` and check them against the goldens in `goldens`, or
+/// write the goldens afresh when `regenerate` is set, giving back the failures
+pub fn check_all(scenarios: &Path, goldens: &Path, regenerate: bool) -> Vec<String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(scenarios)
+        .expect("unable to read scenarios")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut failures = vec![];
+    for path in paths.iter() {
+        let scenario = match Scenario::load(path) {
+            Ok(s) => s,
+            Err(e) => {
+                failures.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let trace = trace(&scenario);
+        let golden = golden_path(goldens, path);
+        if regenerate {
+            fs::create_dir_all(goldens).expect("unable to create goldens");
+            fs::write(&golden, trace.join("\n") + "\n").expect("unable to write golden");
+            continue;
+        }
+        match fs::read_to_string(&golden) {
+            Ok(expected) => {
+                if let Err(e) = compare(&expected, &trace) {
+                    failures.push(format!("{}:\n     {}", path.display(), e));
+                }
+            }
+            Err(e) => failures.push(format!("{}: {}", golden.display(), e)),
+        }
+    }
+    failures
+}
+
+#[test]
+fn trajectories_match_goldens() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let failures = check_all(
+        &root.join("scenarios"),
+        &root.join("goldens"),
+        std::env::var_os(REGENERATE).is_some(),
+    );
+    assert!(
+        failures.is_empty(),
+        "trajectories differ from the goldens, rerun with {}=1 if that is intended\n{}",
+        REGENERATE,
+        failures.join("\n")
+    );
+}
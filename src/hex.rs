@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::coordinate::Coordinate;
+use crate::scent::Scent;
+
+/// The six ways out of a hex cell, angle 0 -> 359
+pub const HEADINGS: [f32; 6] = [0.0, 60.0, 120.0, 180.0, 240.0, 300.0];
+
+/// A cell on the grid in axial coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl Hex {
+    /// The cell next door in the direction of `HEADINGS[heading]`
+    pub fn neighbour(&self, heading: usize) -> Hex {
+        let (q, r) = match heading % 6 {
+            0 => (1, 0),
+            1 => (0, 1),
+            2 => (-1, 1),
+            3 => (-1, 0),
+            4 => (0, -1),
+            _ => (1, -1),
+        };
+        Hex {
+            q: self.q + q,
+            r: self.r + r,
+        }
+    }
+}
+
+/// The closest of the six headings to `direction`, as an index into `HEADINGS`
+pub fn heading(direction: f32) -> usize {
+    (direction / 60.0).round() as usize % 6
+}
+
+/// Pointy topped hex cells laid over the map, used in place of free movement when the config
+/// asks for a hex world
+pub struct HexGrid {
+    /// distance from the centre of a cell to its corners
+    pub size: f32,
+}
+
+impl HexGrid {
+    /// Distance between the centres of neighbouring cells
+    pub fn spacing(&self) -> f32 {
+        self.size * 3f32.sqrt()
+    }
+
+    pub fn centre(&self, hex: Hex) -> Coordinate {
+        Coordinate::new(
+            self.size * 3f32.sqrt() * (hex.q as f32 + hex.r as f32 / 2.0),
+            self.size * 1.5 * hex.r as f32,
+        )
+    }
+
+    /// The cell `pos` lies in
+    pub fn cell(&self, pos: &Coordinate) -> Hex {
+        let q = (3f32.sqrt() / 3.0 * pos.x - pos.y / 3.0) / self.size;
+        let r = (2.0 / 3.0 * pos.y) / self.size;
+        // round in cube coordinates, fixing up whichever moved the most
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        Hex {
+            q: rq as i32,
+            r: rr as i32,
+        }
+    }
+
+    /// The centre of the cell `pos` lies in
+    pub fn snap(&self, pos: &Coordinate) -> Coordinate {
+        self.centre(self.cell(pos))
+    }
+
+    /// Lay a scent at the centre of the cell it falls in, `merge` folds it into any scent already
+    /// there
+    pub fn deposit(&self, scents: &mut Vec<Scent>, scent: Scent) {
+        scents.push(Scent {
+            position: self.snap(&scent.position),
+            ..scent
+        });
+    }
+
+    /// Keep to one scent of each kind per cell, moving those which have drifted back to the
+    /// centre of their cell, where two share a cell the later one freshens up the earlier
+    ///
+    /// # Arguments
+    /// - `fixed` a scent here is left where it is, e.g. the one marking the nest
+    pub fn merge(&self, scents: &mut Vec<Scent>, fixed: Option<&Coordinate>) {
+        let mut cells: HashMap<Hex, usize> = HashMap::new();
+        let mut merged: Vec<Scent> = Vec::with_capacity(scents.len());
+        for s in scents.drain(..) {
+            if fixed == Some(&s.position) {
+                merged.push(s);
+                continue;
+            }
+            let cell = self.cell(&s.position);
+            match cells.get(&cell) {
+                Some(&i) => {
+                    let m = &mut merged[i];
+                    m.life = m.life.max(s.life);
+                    m.direction = s.direction;
+                }
+                None => {
+                    cells.insert(cell, merged.len());
+                    merged.push(Scent {
+                        position: self.centre(cell),
+                        ..s
+                    });
+                }
+            }
+        }
+        *scents = merged;
+    }
+}
+
+#[test]
+fn hex_ants_hop_between_cells() {
+    use crate::config::Config;
+    use crate::world::World;
+
+    let mut world = World::builder()
+        .seed(4)
+        .config(Config {
+            hex_size: 3.0,
+            cluster_spawn_chance: 0.0,
+            // blows scents off the cell centres
+            wind: crate::wind::Wind {
+                speed: 1.0,
+                ..crate::wind::Wind::calm()
+            },
+            ..Config::default()
+        })
+        .build();
+    let grid = world.grid().unwrap();
+    for _ in 0..100 {
+        world.step();
+    }
+
+    assert!(!world.ants.is_empty());
+    for a in world.ants.iter() {
+        assert!(a.position == grid.snap(&a.position));
+    }
+    let home = &world.colonies[0].home_position;
+    let trail = &world.colonies[0].home_scents;
+    assert!(trail.len() > 1);
+    for (i, s) in trail.iter().enumerate() {
+        assert!(s.position == grid.snap(&s.position) || s.position == *home);
+        assert!(trail[i + 1..].iter().all(|t| t.position != s.position));
+    }
+}
@@ -0,0 +1,577 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+
+use crate::ant::Ant;
+use crate::brood::{Brood, Queen};
+use crate::colony::Colony;
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::corpse::Corpse;
+use crate::food::{FoodKind, Patch, PerFood};
+use crate::metrics::Metrics;
+use crate::rng;
+use crate::scent::Scent;
+use crate::world::World;
+
+/// Ticks of history kept by default
+pub const HISTORY_TICKS: u64 = 3000;
+/// Ticks between full snapshots by default
+pub const KEYFRAME_INTERVAL: u64 = 100;
+
+/// The small bits of a colony which are stored in full every tick
+#[derive(Clone)]
+struct ColonyTotals {
+    stores: PerFood<u32>,
+    queen: Option<Queen>,
+}
+
+/// The small bits of world state which are stored in full every tick
+#[derive(Clone)]
+struct Totals {
+    tick: u64,
+    colonies: Vec<ColonyTotals>,
+    metrics: Metrics,
+    next_ant_id: u64,
+    rng: StdRng,
+}
+
+impl Totals {
+    fn capture(world: &World) -> Totals {
+        Totals {
+            tick: world.tick,
+            colonies: world
+                .colonies
+                .iter()
+                .map(|c| ColonyTotals {
+                    stores: c.stores.clone(),
+                    queen: c.queen.clone(),
+                })
+                .collect(),
+            metrics: world.metrics.clone(),
+            next_ant_id: world.next_ant_id,
+            rng: rng::state(),
+        }
+    }
+
+    fn restore(&self, world: &mut World) {
+        world.tick = self.tick;
+        for (c, totals) in world.colonies.iter_mut().zip(self.colonies.iter()) {
+            c.stores = totals.stores.clone();
+            c.queen = totals.queen.clone();
+        }
+        world.metrics = self.metrics.clone();
+        world.next_ant_id = self.next_ant_id;
+        rng::restore(self.rng.clone());
+    }
+}
+
+/// The whole world at a point in time
+struct Snapshot {
+    totals: Totals,
+    ants: Vec<Ant>,
+    food_positions: PerFood<Vec<Coordinate>>,
+    colonies: Vec<Colony>,
+    necromone: Vec<Scent>,
+    corpses: Vec<Corpse>,
+    patches: Vec<Patch>,
+}
+
+/// The parts of an ant which change as it goes about its business, its id, colony, caste and
+/// brain stay the same for life
+#[derive(Debug, Clone, PartialEq)]
+struct AntState {
+    position: Coordinate,
+    direction: f32,
+    has_food: bool,
+    food_kind: FoodKind,
+    corpse: Option<Corpse>,
+    health: f32,
+    speed: f32,
+    life: u32,
+    previous_position: Coordinate,
+    previous_direction: f32,
+    distance_walked: f32,
+    fatigue: f32,
+    bridging: u32,
+}
+
+impl AntState {
+    fn of(a: &Ant) -> AntState {
+        AntState {
+            position: a.position.clone(),
+            direction: a.direction,
+            has_food: a.has_food,
+            food_kind: a.food_kind,
+            corpse: a.corpse.clone(),
+            health: a.health,
+            speed: a.speed,
+            life: a.life,
+            previous_position: a.previous_position.clone(),
+            previous_direction: a.previous_direction,
+            distance_walked: a.distance_walked,
+            fatigue: a.fatigue,
+            bridging: a.bridging,
+        }
+    }
+
+    fn restore(&self, a: &mut Ant) {
+        a.position = self.position.clone();
+        a.direction = self.direction;
+        a.has_food = self.has_food;
+        a.food_kind = self.food_kind;
+        a.corpse = self.corpse.clone();
+        a.health = self.health;
+        a.speed = self.speed;
+        a.life = self.life;
+        a.previous_position = self.previous_position.clone();
+        a.previous_direction = self.previous_direction;
+        a.distance_walked = self.distance_walked;
+        a.fatigue = self.fatigue;
+        a.bridging = self.bridging;
+    }
+}
+
+/// How one of a colony's trails changed over a single tick
+enum NewScents {
+    /// the scents laid this tick
+    Added(Vec<Scent>),
+    /// scents which drift or are freshened up in place change every tick, so the whole trail is kept
+    Replaced(Vec<Scent>),
+}
+
+/// How many of a colony's scents will still be around after the next decay, one entry per trail,
+/// None for those which drift or, on a hex grid, are freshened up in place
+struct Survivors(Vec<Option<usize>>);
+
+impl Survivors {
+    fn count(colony: &Colony, config: &Config) -> Survivors {
+        Survivors(
+            colony
+                .trails()
+                .into_iter()
+                .map(|(kind, scents)| {
+                    let pheromone = config.pheromone(kind);
+                    if pheromone.diffusion > 0.0 || config.hex_size > 0.0 {
+                        None
+                    } else {
+                        Some(scents.iter().filter(|s| pheromone.survives(s)).count())
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn new_scents(&self, colony: &Colony) -> Vec<NewScents> {
+        self.0
+            .iter()
+            .zip(colony.trails())
+            .map(|(survivors, (_, scents))| match survivors {
+                Some(n) => NewScents::Added(scents[*n..].to_vec()),
+                None => NewScents::Replaced(scents.clone()),
+            })
+            .collect()
+    }
+}
+
+/// The world as it was before a tick, to work out what the tick changed
+struct Before {
+    ants: Vec<(u64, AntState)>,
+    /// a tick older, as they would be if nothing else happened to them
+    corpses: Vec<Corpse>,
+    brood: Vec<Vec<Brood>>,
+    patches: Vec<Patch>,
+    food_positions: PerFood<Vec<Coordinate>>,
+}
+
+impl Before {
+    fn capture(world: &World) -> Before {
+        Before {
+            ants: world.ants.iter().map(|a| (a.id, AntState::of(a))).collect(),
+            corpses: world.corpses.iter().map(aged_corpse).collect(),
+            brood: world
+                .colonies
+                .iter()
+                .map(|c| c.brood.iter().map(aged_brood).collect())
+                .collect(),
+            patches: world.patches.clone(),
+            food_positions: world.food_positions.clone(),
+        }
+    }
+}
+
+fn aged_corpse(c: &Corpse) -> Corpse {
+    Corpse {
+        age: c.age + 1,
+        ..c.clone()
+    }
+}
+
+fn aged_brood(b: &Brood) -> Brood {
+    Brood {
+        age: b.age + 1,
+        ..b.clone()
+    }
+}
+
+/// What changed over a single tick
+///
+/// Scents are not stored as they only ever decay, apart from the new ones laid this tick. Ants
+/// and corpses are only ever removed from the middle and added to the end, so only those which
+/// changed are kept, while brood and patches are kept whole on the odd tick something happens
+/// to them other than getting older
+struct Delta {
+    totals: Totals,
+    /// indices into the ants as they were before the tick, ascending
+    ants_removed: Vec<usize>,
+    /// indices into the ants left after those removed, for those which did anything
+    ants_changed: Vec<(usize, AntState)>,
+    /// new ants, the only ones stored whole
+    ants_added: Vec<Ant>,
+    /// indices into the corpses as they were before the tick, ascending, the rest age a tick
+    corpses_removed: Vec<usize>,
+    corpses_added: Vec<Corpse>,
+    /// per colony, None if it all just aged a tick
+    brood: Vec<Option<Vec<Brood>>>,
+    /// None if no patch changed
+    patches: Option<Vec<Patch>>,
+    /// per colony, per trail
+    new_scents: Vec<Vec<NewScents>>,
+    new_necromone: Vec<Scent>,
+    /// indices into the food as it was before the tick, ascending
+    food_removed: PerFood<Vec<usize>>,
+    food_added: PerFood<Vec<Coordinate>>,
+}
+
+/// A snapshot followed by the deltas for the ticks after it
+struct Segment {
+    keyframe: Snapshot,
+    deltas: Vec<Delta>,
+}
+
+impl Segment {
+    fn last_tick(&self) -> u64 {
+        self.keyframe.totals.tick + self.deltas.len() as u64
+    }
+}
+
+/// A rolling record of the world, used to scrub back and forth through recent ticks
+pub struct History {
+    capacity: u64,
+    keyframe_interval: u64,
+    segments: VecDeque<Segment>,
+}
+
+impl History {
+    /// Start recording from the world as it is now
+    pub fn new(world: &World) -> History {
+        History::with_capacity(world, HISTORY_TICKS, KEYFRAME_INTERVAL)
+    }
+
+    /// # Arguments
+    /// - `capacity` how many ticks to keep
+    /// - `keyframe_interval` how many ticks between full snapshots
+    pub fn with_capacity(world: &World, capacity: u64, keyframe_interval: u64) -> History {
+        let mut segments = VecDeque::new();
+        segments.push_back(Segment {
+            keyframe: capture(world),
+            deltas: vec![],
+        });
+        History {
+            capacity,
+            keyframe_interval: keyframe_interval.max(1),
+            segments,
+        }
+    }
+
+    pub fn oldest_tick(&self) -> u64 {
+        // SAFE: there is always at least one segment
+        self.segments.front().unwrap().keyframe.totals.tick
+    }
+
+    pub fn newest_tick(&self) -> u64 {
+        // SAFE: there is always at least one segment
+        self.segments.back().unwrap().last_tick()
+    }
+
+    /// Step the world forwards and record what happened, nothing happens once the invariant
+    /// checks have stopped the world
+    pub fn step(&mut self, world: &mut World) {
+        if world.violation.is_some() {
+            return;
+        }
+        let survivors: Vec<Survivors> = world
+            .colonies
+            .iter()
+            .map(|c| Survivors::count(c, &world.config))
+            .collect();
+        let necromone_survivors = world.necromone.iter().filter(|s| s.life > 1).count();
+        let before = Before::capture(world);
+
+        world.step();
+
+        // SAFE: there is always at least one segment
+        let current = self.segments.back_mut().unwrap();
+        if current.deltas.len() as u64 + 1 >= self.keyframe_interval {
+            self.segments.push_back(Segment {
+                keyframe: capture(world),
+                deltas: vec![],
+            });
+        } else {
+            let food_diff =
+                PerFood::new(|k| diff(&before.food_positions[k], &world.food_positions[k]));
+            let (ants_removed, ants_changed, ants_added) = diff_ants(&before.ants, &world.ants);
+            let (corpses_removed, corpses_added) = diff(&before.corpses, &world.corpses);
+            current.deltas.push(Delta {
+                totals: Totals::capture(world),
+                ants_removed,
+                ants_changed,
+                ants_added,
+                corpses_removed,
+                corpses_added,
+                brood: before
+                    .brood
+                    .iter()
+                    .zip(world.colonies.iter())
+                    .map(|(aged, c)| (*aged != c.brood).then(|| c.brood.clone()))
+                    .collect(),
+                patches: (before.patches != world.patches).then(|| world.patches.clone()),
+                new_scents: survivors
+                    .iter()
+                    .zip(world.colonies.iter())
+                    .map(|(s, c)| s.new_scents(c))
+                    .collect(),
+                new_necromone: world.necromone[necromone_survivors..].to_vec(),
+                food_removed: PerFood::new(|k| food_diff[k].0.clone()),
+                food_added: PerFood::new(|k| food_diff[k].1.clone()),
+            });
+        }
+
+        while self.segments.len() > 1 && self.newest_tick() - self.oldest_tick() > self.capacity {
+            self.segments.pop_front();
+        }
+    }
+
+    /// Put the world back how it was at `tick`, returns false if that tick isn't recorded
+    ///
+    /// Any invariant violation is cleared, it is found again if the world steps on from where it
+    /// broke
+    pub fn seek(&self, world: &mut World, tick: u64) -> bool {
+        if tick < self.oldest_tick() || tick > self.newest_tick() {
+            return false;
+        }
+        // SAFE: the range check guarantees a segment starts at or before tick
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|s| s.keyframe.totals.tick <= tick)
+            .unwrap();
+
+        let keyframe = &segment.keyframe;
+        world.violation = None;
+        keyframe.totals.restore(world);
+        world.ants = keyframe.ants.clone();
+        world.food_positions = keyframe.food_positions.clone();
+        world.colonies = keyframe.colonies.clone();
+        world.necromone = keyframe.necromone.clone();
+        world.corpses = keyframe.corpses.clone();
+        world.patches = keyframe.patches.clone();
+
+        let count = (tick - keyframe.totals.tick) as usize;
+        for delta in segment.deltas.iter().take(count) {
+            apply(world, delta);
+        }
+        true
+    }
+
+    /// Forget everything after `tick`, so that the simulation can carry on from there
+    pub fn truncate(&mut self, tick: u64) {
+        while self.segments.len() > 1 && self.segments.back().unwrap().keyframe.totals.tick > tick {
+            self.segments.pop_back();
+        }
+        // SAFE: there is always at least one segment
+        let current = self.segments.back_mut().unwrap();
+        let keep = tick.saturating_sub(current.keyframe.totals.tick) as usize;
+        current.deltas.truncate(keep);
+    }
+}
+
+fn capture(world: &World) -> Snapshot {
+    Snapshot {
+        totals: Totals::capture(world),
+        ants: world.ants.clone(),
+        food_positions: world.food_positions.clone(),
+        colonies: world.colonies.clone(),
+        necromone: world.necromone.clone(),
+        corpses: world.corpses.clone(),
+        patches: world.patches.clone(),
+    }
+}
+
+fn apply(world: &mut World, delta: &Delta) {
+    // the wind depends on the tick
+    world.tick = delta.totals.tick;
+    for i in delta.ants_removed.iter().rev() {
+        world.ants.remove(*i);
+    }
+    for (i, state) in delta.ants_changed.iter() {
+        state.restore(&mut world.ants[*i]);
+    }
+    world.ants.extend(delta.ants_added.iter().cloned());
+    for i in delta.corpses_removed.iter().rev() {
+        world.corpses.remove(*i);
+    }
+    for c in world.corpses.iter_mut() {
+        c.age += 1;
+    }
+    world.corpses.extend(delta.corpses_added.iter().cloned());
+    for (c, brood) in world.colonies.iter_mut().zip(delta.brood.iter()) {
+        match brood {
+            Some(brood) => c.brood = brood.clone(),
+            None => c.brood.iter_mut().for_each(|b| b.age += 1),
+        }
+    }
+    if let Some(patches) = &delta.patches {
+        world.patches = patches.clone();
+    }
+    world.decay_scents();
+    for (c, new) in world.colonies.iter_mut().zip(delta.new_scents.iter()) {
+        for ((_, scents), new) in c.trails_mut().into_iter().zip(new.iter()) {
+            match new {
+                NewScents::Added(added) => scents.extend(added.iter().cloned()),
+                NewScents::Replaced(all) => *scents = all.clone(),
+            }
+        }
+    }
+    world.necromone.extend(delta.new_necromone.iter().cloned());
+    for kind in FoodKind::ALL.iter() {
+        for i in delta.food_removed[*kind].iter().rev() {
+            world.food_positions[*kind].remove(*i);
+        }
+        world.food_positions[*kind].extend(delta.food_added[*kind].iter().cloned());
+    }
+    // last, as drifting scents use up random numbers while decaying
+    delta.totals.restore(world);
+}
+
+/// Work out which things were removed and which were added, for food, corpses and the like which
+/// are only ever removed from the middle and added to the end
+fn diff<T: Clone + PartialEq>(before: &[T], after: &[T]) -> (Vec<usize>, Vec<T>) {
+    let mut removed = vec![];
+    let mut j = 0;
+    for (i, f) in before.iter().enumerate() {
+        if after.get(j) == Some(f) {
+            j += 1;
+        } else {
+            removed.push(i);
+        }
+    }
+    (removed, after[j..].to_vec())
+}
+
+/// Work out which ants died, which did something and which are new
+fn diff_ants(
+    before: &[(u64, AntState)],
+    after: &[Ant],
+) -> (Vec<usize>, Vec<(usize, AntState)>, Vec<Ant>) {
+    let mut removed = vec![];
+    let mut changed = vec![];
+    let mut j = 0;
+    for (i, (id, state)) in before.iter().enumerate() {
+        match after.get(j).filter(|a| a.id == *id) {
+            Some(a) => {
+                let now = AntState::of(a);
+                if now != *state {
+                    changed.push((j, now));
+                }
+                j += 1;
+            }
+            None => removed.push(i),
+        }
+    }
+    (removed, changed, after[j..].to_vec())
+}
+
+#[test]
+fn history_seek_matches_the_past() {
+    let summary = |w: &World| {
+        (
+            w.tick,
+            w.ants
+                .iter()
+                .map(|a| (a.id, AntState::of(a)))
+                .collect::<Vec<_>>(),
+            w.food_positions.clone(),
+            w.corpses.clone(),
+            w.colonies[0].brood.clone(),
+            w.patches.clone(),
+            w.colonies[0].home_scents.len(),
+            w.colonies[0].food_scents.sugar.len(),
+            w.colonies[0].food_scents.protein.len(),
+            w.colonies[0].stores.clone(),
+        )
+    };
+
+    rng::seed(3);
+    let mut world = World::new();
+    world.populate();
+    // ants dying one after another, so there are corpses lying about
+    for (i, a) in world.ants.iter_mut().enumerate() {
+        a.life = 95 + i as u32 * 5;
+    }
+    let mut history = History::with_capacity(&world, 1000, 100);
+    let mut at_135 = None;
+    for _ in 0..250 {
+        history.step(&mut world);
+        if world.tick == 135 {
+            at_135 = Some(summary(&world));
+        }
+    }
+
+    assert!(history.seek(&mut world, 135));
+    assert_eq!(Some(summary(&world)), at_135);
+
+    // carrying on from the past should play out the same way again
+    history.truncate(135);
+    for _ in 135..250 {
+        history.step(&mut world);
+    }
+    let at_250 = summary(&world);
+    assert!(history.seek(&mut world, 135));
+    for _ in 135..250 {
+        world.step();
+    }
+    assert_eq!(summary(&world), at_250);
+}
+
+#[test]
+fn seeking_back_from_a_violation_carries_on() {
+    use crate::config::Config;
+
+    let mut world = World::builder()
+        .seed(6)
+        .config(Config {
+            check_invariants: true,
+            ..Config::default()
+        })
+        .build();
+    let mut history = History::new(&world);
+    for _ in 0..50 {
+        history.step(&mut world);
+    }
+    world.food_positions.sugar.pop();
+    history.step(&mut world);
+    assert!(world.violation.is_some());
+    history.step(&mut world);
+    assert_eq!(history.newest_tick(), 51);
+
+    assert!(history.seek(&mut world, 20));
+    assert!(world.violation.is_none());
+    history.truncate(20);
+    for _ in 0..10 {
+        history.step(&mut world);
+    }
+    assert!(world.violation.is_none());
+    assert_eq!(world.tick, 30);
+}
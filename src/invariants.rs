@@ -0,0 +1,230 @@
+use std::fmt;
+
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::pheromone::PheromoneKind;
+use crate::scent::Scent;
+use crate::world::{World, X_SIZE, Y_SIZE};
+
+/// Counters above this have gone below zero and wrapped round, the nest scent which never fades
+/// is the one thing allowed up here
+const WRAPPED: u32 = u32::MAX / 2;
+
+/// The first thing found wrong with the world, along with what it was found in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub tick: u64,
+    /// what broke the rule, e.g. "ant 12" or "colony 0 alarm scent 3"
+    pub entity: String,
+    pub problem: String,
+    /// everything about the entity which might help track it down
+    pub context: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invariant broken on tick {}: {} {}\n{}",
+            self.tick, self.entity, self.problem, self.context
+        )
+    }
+}
+
+/// Food in the world less all that has come into it and plus all that has gone, this stays the
+/// same from tick to tick unless food appears or vanishes somewhere it shouldn't
+pub fn food_ledger(world: &World) -> i64 {
+    let m = &world.metrics;
+    let on_map = world.food_positions.sugar.len() + world.food_positions.protein.len();
+    let carried = world.ants.iter().filter(|a| a.has_food).count();
+    let stored: u32 = world
+        .colonies
+        .iter()
+        .map(|c| c.stores.sugar + c.stores.protein)
+        .sum();
+    let rotted = m.corpses_decayed * world.config.corpse_food as u64;
+    (on_map + carried) as i64 + stored as i64 - m.food_grown as i64 - rotted as i64
+        + m.food_eaten as i64
+        + m.food_lost as i64
+}
+
+/// What is wrong with a position, if anything
+fn bad_position(pos: &Coordinate) -> Option<&'static str> {
+    if !pos.x.is_finite() || !pos.y.is_finite() {
+        Some("has a position which isn't a number")
+    } else if pos.x < 0.0 || pos.x > X_SIZE || pos.y < 0.0 || pos.y > Y_SIZE {
+        Some("is off the map")
+    } else {
+        None
+    }
+}
+
+fn describe(s: &Scent) -> String {
+    format!(
+        "at {:?} direction {} life {}",
+        s.position, s.direction, s.life
+    )
+}
+
+fn bad_direction(direction: f32) -> Option<&'static str> {
+    if !direction.is_finite() {
+        Some("has a direction which isn't a number")
+    } else if !(0.0..360.0).contains(&direction) {
+        Some("has a direction outside 0 -> 360")
+    } else {
+        None
+    }
+}
+
+/// Check the world for NaNs, headings out of range, counters which have wrapped below zero,
+/// things off the map and food which has appeared or vanished against `ledger`, stopping at the
+/// first problem
+pub fn check(world: &World, ledger: i64) -> Result<(), Violation> {
+    let violation = |entity: String, problem: &str, context: String| Violation {
+        tick: world.tick,
+        entity,
+        problem: problem.to_string(),
+        context,
+    };
+
+    for a in world.ants.iter() {
+        let problem = bad_position(&a.position)
+            .or_else(|| bad_direction(a.direction))
+            .or_else(|| (a.life > WRAPPED).then_some("has wrapped its life below zero"))
+            .or_else(|| {
+                (!a.health.is_finite() || !a.speed.is_finite())
+                    .then_some("has health or speed which isn't a number")
+            });
+        if let Some(problem) = problem {
+            return Err(violation(
+                format!("ant {}", a.id),
+                problem,
+                format!(
+                    "colony {} at {:?} was at {:?}, direction {} speed {} life {} health {} \
+                     has food {} bridging {}",
+                    a.colony,
+                    a.position,
+                    a.previous_position,
+                    a.direction,
+                    a.speed,
+                    a.life,
+                    a.health,
+                    a.has_food,
+                    a.bridging
+                ),
+            ));
+        }
+    }
+
+    for c in world.colonies.iter() {
+        for kind in FoodKind::ALL.iter() {
+            if c.stores[*kind] > WRAPPED {
+                return Err(violation(
+                    format!("colony {}", c.id),
+                    "has wrapped its stores below zero",
+                    format!("stores {:?}", c.stores),
+                ));
+            }
+        }
+        if let Some(q) = c.queen.as_ref().filter(|q| q.life > WRAPPED) {
+            return Err(violation(
+                format!("queen {}", q.id),
+                "has wrapped her life below zero",
+                format!("colony {} life {}", c.id, q.life),
+            ));
+        }
+        for (kind, scents) in c.trails() {
+            for (i, s) in scents.iter().enumerate() {
+                let permanent = kind == PheromoneKind::Home && s.position == c.home_position;
+                let problem = bad_position(&s.position)
+                    .or_else(|| bad_direction(s.direction))
+                    .or_else(|| {
+                        (s.life > WRAPPED && !permanent)
+                            .then_some("has wrapped its life below zero")
+                    });
+                if let Some(problem) = problem {
+                    return Err(violation(
+                        format!("colony {} {} scent {}", c.id, kind.name(), i),
+                        problem,
+                        describe(s),
+                    ));
+                }
+            }
+        }
+    }
+
+    for (i, s) in world.necromone.iter().enumerate() {
+        if let Some(problem) = bad_position(&s.position) {
+            return Err(violation(format!("necromone {}", i), problem, describe(s)));
+        }
+    }
+    for kind in FoodKind::ALL.iter() {
+        for (i, f) in world.food_positions[*kind].iter().enumerate() {
+            if let Some(problem) = bad_position(f) {
+                return Err(violation(
+                    format!("{:?} food {}", kind, i),
+                    problem,
+                    format!("{:?}", f),
+                ));
+            }
+        }
+    }
+    for (i, c) in world.corpses.iter().enumerate() {
+        if let Some(problem) = bad_position(&c.position) {
+            return Err(violation(
+                format!("corpse {}", i),
+                problem,
+                format!("{:?}", c),
+            ));
+        }
+    }
+
+    let now = food_ledger(world);
+    if now != ledger {
+        return Err(violation(
+            "food".to_string(),
+            "isn't conserved",
+            format!(
+                "{} more pieces than there should be, {:?}",
+                now - ledger,
+                world.metrics
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn broken_worlds_are_caught() {
+    use crate::config::Config;
+
+    let mut world = World::builder()
+        .seed(6)
+        .config(Config {
+            check_invariants: true,
+            ..Config::default()
+        })
+        .build();
+    for _ in 0..300 {
+        world.step();
+    }
+    let ledger = food_ledger(&world);
+    assert_eq!(check(&world, ledger), Ok(()));
+
+    let id = world.ants[0].id;
+    world.ants[0].direction = f32::NAN;
+    let v = check(&world, ledger).unwrap_err();
+    assert_eq!(v.entity, format!("ant {}", id));
+
+    world.ants[0].direction = 90.0;
+    world.food_positions.sugar.pop();
+    let v = check(&world, ledger).unwrap_err();
+    assert_eq!(v.entity, "food");
+
+    // stepping finds it too, and the world stops there
+    world.step();
+    assert_eq!(world.violation.as_ref().unwrap().entity, "food");
+    let tick = world.tick;
+    world.step();
+    assert_eq!(world.tick, tick);
+}
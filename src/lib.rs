@@ -0,0 +1,48 @@
+pub mod analytics;
+pub mod ant;
+pub mod brain;
+pub mod brood;
+pub mod colony;
+pub mod combat;
+pub mod config;
+pub mod coordinate;
+pub mod corpse;
+pub mod env;
+pub mod events;
+pub mod food;
+pub mod golden;
+pub mod hex;
+pub mod history;
+pub mod invariants;
+pub mod logging;
+pub mod metrics;
+pub mod navigable;
+pub mod pheromone;
+pub mod profiler;
+pub mod rng;
+pub mod scenario;
+pub mod scent;
+pub mod terrain;
+pub mod wall;
+pub mod water;
+pub mod wind;
+pub mod world;
+
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "server")]
+pub mod server;
+
+pub use ant::Ant;
+pub use brain::{Action, AntBrain, Perception};
+pub use colony::Colony;
+pub use config::Config;
+pub use coordinate::Coordinate;
+pub use events::{Event, EventBus};
+pub use food::{FoodKind, PerFood};
+pub use metrics::Metrics;
+pub use navigable::Navigable;
+pub use pheromone::{Pheromone, PheromoneKind, Response};
+pub use scent::Scent;
+pub use wind::Wind;
+pub use world::{World, WorldBuilder};
@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+/// Verbosity when neither the caller nor `RUST_LOG` say otherwise
+pub const DEFAULT_LEVEL: &str = "warn";
+
+/// Send logs to stderr and, if `json` is given, to that file as one JSON object per line
+///
+/// # Arguments
+/// - `level` anything `RUST_LOG` accepts, e.g. "debug" or "warn,ants::world=trace", falls back
+///   on `RUST_LOG` and then `DEFAULT_LEVEL`
+pub fn init(level: Option<&str>, json: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let filter = match level {
+        Some(level) => EnvFilter::try_new(level)?,
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LEVEL)),
+    };
+    let json = match json {
+        Some(path) => Some(
+            fmt::layer()
+                .json()
+                .with_writer(Mutex::new(File::create(path)?)),
+        ),
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(io::stderr))
+        .with(json)
+        .try_init()?;
+    Ok(())
+}
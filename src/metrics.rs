@@ -0,0 +1,24 @@
+/// Running totals kept by the world over the course of a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub food_collected: u64,
+    pub food_delivered: u64,
+    pub ants_spawned: u64,
+    pub ants_died: u64,
+    /// ants bitten to death in fights
+    pub ants_killed: u64,
+    /// ants which walked into water alone
+    pub ants_drowned: u64,
+    pub eggs_laid: u64,
+    /// larvae which starved before they could pupate
+    pub brood_died: u64,
+    /// food which has appeared since the world was populated, not counting dead ants
+    pub food_grown: u64,
+    /// food used up laying eggs and feeding larvae
+    pub food_eaten: u64,
+    /// food carried off by ants which drowned or wandered off the map
+    pub food_lost: u64,
+    /// corpses carried to the midden
+    pub corpses_buried: u64,
+    pub corpses_decayed: u64,
+}
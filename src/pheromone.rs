@@ -0,0 +1,175 @@
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::coordinate::Coordinate;
+use crate::rng;
+use crate::scent::{Scent, SCENT_LIFE};
+use crate::world::{X_SIZE, Y_SIZE};
+
+/// The pheromones an ant is able to lay down, food trails are kept apart by the kind of food
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PheromoneKind {
+    Home,
+    Food,
+    /// short lived, points towards a fight rather than back along the trail
+    Alarm,
+    /// marks a path to food which has run out, so others don't waste the trip
+    Repellent,
+    /// laid by ants searching off the trails, marks ground which has already been covered
+    Exploration,
+}
+
+impl PheromoneKind {
+    pub const ALL: [PheromoneKind; 5] = [
+        PheromoneKind::Home,
+        PheromoneKind::Food,
+        PheromoneKind::Alarm,
+        PheromoneKind::Repellent,
+        PheromoneKind::Exploration,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PheromoneKind::Home => "home",
+            PheromoneKind::Food => "food",
+            PheromoneKind::Alarm => "alarm",
+            PheromoneKind::Repellent => "repellent",
+            PheromoneKind::Exploration => "exploration",
+        }
+    }
+}
+
+/// What an ant does when it smells a pheromone
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int))]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Response {
+    /// go where the scent points
+    Follow,
+    /// head away from the scent, for alarm, repellent and exploration scents, home and food
+    /// trails set to this are treated as `Ignore`, see `brain::trail`
+    Avoid,
+    Ignore,
+}
+
+/// How a kind of pheromone behaves once it has been laid and how ants take to it
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Pheromone {
+    /// ticks of life a fresh scent starts with
+    pub life: u32,
+    /// life lost each tick
+    pub decay: u32,
+    /// how far a scent drifts about each tick, 0 keeps it where it was laid
+    pub diffusion: f32,
+    /// red, green, blue used to draw it
+    pub colour: (u8, u8, u8),
+    pub response: Response,
+}
+
+impl Pheromone {
+    /// A fresh scent at `position` pointing in `direction`
+    pub fn lay(&self, position: &Coordinate, direction: f32) -> Scent {
+        Scent {
+            position: position.clone(),
+            direction,
+            life: self.life,
+        }
+    }
+
+    /// Age a scent by a tick and let it drift, returns false once it has faded away
+    pub fn age(&self, scent: &mut Scent) -> bool {
+        scent.life = scent.life.saturating_sub(self.decay.max(1));
+        if self.diffusion > 0.0 {
+            let (angle, dist) = rng::with(|r| (r.gen::<f32>() * 359.9, r.gen::<f32>()));
+            scent.position = scent
+                .position
+                .traverse_direction(angle, dist * self.diffusion)
+                .check_bounds(0.0, X_SIZE, 0.0, Y_SIZE);
+        }
+        scent.life > 0
+    }
+
+    /// True if a scent will still be around after the next tick
+    pub fn survives(&self, scent: &Scent) -> bool {
+        scent.life > self.decay.max(1)
+    }
+
+    pub fn home() -> Pheromone {
+        Pheromone {
+            life: SCENT_LIFE / 2,
+            decay: 1,
+            diffusion: 0.0,
+            colour: (0, 44, 190),
+            response: Response::Follow,
+        }
+    }
+
+    pub fn food() -> Pheromone {
+        Pheromone {
+            life: SCENT_LIFE / 2,
+            decay: 1,
+            diffusion: 0.0,
+            colour: (190, 190, 0),
+            response: Response::Follow,
+        }
+    }
+
+    /// Spreads quickly and is gone within a minute, nestmates come to help
+    pub fn alarm() -> Pheromone {
+        Pheromone {
+            life: 60,
+            decay: 1,
+            diffusion: 1.0,
+            colour: (255, 60, 0),
+            response: Response::Follow,
+        }
+    }
+
+    pub fn repellent() -> Pheromone {
+        Pheromone {
+            life: 400,
+            decay: 1,
+            diffusion: 0.0,
+            colour: (60, 60, 60),
+            response: Response::Avoid,
+        }
+    }
+
+    /// Off by default, searching ants only lay it if others will take any notice
+    pub fn exploration() -> Pheromone {
+        Pheromone {
+            life: 150,
+            decay: 1,
+            diffusion: 0.0,
+            colour: (150, 220, 150),
+            response: Response::Ignore,
+        }
+    }
+}
+
+/// The closest scent within `range` of `pos`
+pub fn nearest<'s>(scents: &'s Vec<Scent>, pos: &Coordinate, range: f32) -> Option<&'s Scent> {
+    scents
+        .iter()
+        .filter(|s| s.position.dist(pos) < range)
+        .min_by(|a, b| a.position.dist(pos).total_cmp(&b.position.dist(pos)))
+}
+
+#[test]
+fn diffusing_scents_drift_as_they_fade() {
+    rng::seed(1);
+    let alarm = Pheromone::alarm();
+    let still = Pheromone::home();
+    let start = Coordinate::new(250.0, 250.0);
+    let mut a = alarm.lay(&start, 0.0);
+    let mut s = still.lay(&start, 0.0);
+    for _ in 1..alarm.life {
+        assert!(alarm.age(&mut a));
+        assert!(still.age(&mut s));
+    }
+
+    assert!(!alarm.age(&mut a));
+    assert!(a.position != start);
+    assert!(s.position == start);
+}
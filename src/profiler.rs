@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Ticks or frames the overlay averages over
+const WINDOW: usize = 60;
+/// Most spans kept for the trace, later ones are dropped so a long run can't eat all the memory
+const MAX_SPANS: usize = 1_000_000;
+
+/// The parts a tick and a frame are broken into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// laying eggs and raising brood
+    Spawn,
+    ScentDecay,
+    /// new clusters appearing and patches growing back
+    FoodGrowth,
+    /// looking about and deciding what to do
+    Sensing,
+    /// everything an ant does other than handling food
+    Movement,
+    FoodPickup,
+    /// corpses rotting, fights settling and the dead being cleared away
+    Cleanup,
+    MeshBuild,
+    Draw,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 9] = [
+        Phase::Spawn,
+        Phase::ScentDecay,
+        Phase::FoodGrowth,
+        Phase::Sensing,
+        Phase::Movement,
+        Phase::FoodPickup,
+        Phase::Cleanup,
+        Phase::MeshBuild,
+        Phase::Draw,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Spawn => "spawn",
+            Phase::ScentDecay => "scent decay",
+            Phase::FoodGrowth => "food growth",
+            Phase::Sensing => "sensing",
+            Phase::Movement => "movement",
+            Phase::FoodPickup => "food pickup",
+            Phase::Cleanup => "cleanup",
+            Phase::MeshBuild => "render mesh build",
+            Phase::Draw => "draw",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Times each phase of the simulation and rendering, doing nothing until enabled
+pub struct Profiler {
+    pub enabled: bool,
+    /// keep every span for `chrome_trace`, not just the running averages
+    pub trace: bool,
+    origin: Instant,
+    /// time in each phase since the last `finish`, and whether it ran at all
+    current: [Option<Duration>; 9],
+    /// the last `WINDOW` totals for each phase
+    recent: Vec<VecDeque<Duration>>,
+    /// phase, start since the profiler was made and length
+    spans: Vec<(Phase, Duration, Duration)>,
+}
+
+/// Adds the time until it is dropped onto a phase, see `Profiler::scope`
+pub struct Scope<'p> {
+    profiler: &'p mut Profiler,
+    phase: Phase,
+    start: Option<Instant>,
+}
+
+impl<'p> Drop for Scope<'p> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.profiler.add(self.phase, start.elapsed());
+        }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            enabled: false,
+            trace: false,
+            origin: Instant::now(),
+            current: [None; 9],
+            recent: vec![VecDeque::new(); Phase::ALL.len()],
+            spans: vec![],
+        }
+    }
+
+    /// The time to pass to `stop`, None when profiling is off so it costs next to nothing
+    pub fn start(&self) -> Option<Instant> {
+        if self.enabled {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Put the time since `start` down to a phase, and keep it as a span if tracing
+    pub fn stop(&mut self, phase: Phase, start: Option<Instant>) {
+        if let Some(start) = start {
+            let elapsed = start.elapsed();
+            self.add(phase, elapsed);
+            self.span(phase, start, elapsed);
+        }
+    }
+
+    /// Time a small piece of work which happens many times over, e.g. once per ant, the total is
+    /// kept but not each span, see `lay_out`
+    pub fn scope(&mut self, phase: Phase) -> Scope<'_> {
+        let start = self.start();
+        Scope {
+            profiler: self,
+            phase,
+            start,
+        }
+    }
+
+    /// Record spans for phases timed with `scope` since `start`, they are interleaved so are
+    /// shown back to back in the trace
+    pub fn lay_out(&mut self, start: Option<Instant>, phases: &[Phase]) {
+        if let Some(mut at) = start {
+            for phase in phases {
+                let elapsed = self.current[phase.index()].unwrap_or_default();
+                self.span(*phase, at, elapsed);
+                at += elapsed;
+            }
+        }
+    }
+
+    fn add(&mut self, phase: Phase, elapsed: Duration) {
+        let total = &mut self.current[phase.index()];
+        *total = Some(total.unwrap_or_default() + elapsed);
+    }
+
+    fn span(&mut self, phase: Phase, start: Instant, elapsed: Duration) {
+        if self.trace && self.spans.len() < MAX_SPANS {
+            let start = start.duration_since(self.origin);
+            self.spans.push((phase, start, elapsed));
+        }
+    }
+
+    /// Roll the phases timed since the last call into the averages, called at the end of every
+    /// tick and every frame
+    pub fn finish(&mut self) {
+        for (total, recent) in self.current.iter_mut().zip(self.recent.iter_mut()) {
+            if let Some(t) = total.take() {
+                if recent.len() == WINDOW {
+                    recent.pop_front();
+                }
+                recent.push_back(t);
+            }
+        }
+    }
+
+    /// Average time each phase has taken over the last few ticks or frames it ran in
+    pub fn averages(&self) -> Vec<(Phase, Duration)> {
+        Phase::ALL
+            .iter()
+            .zip(self.recent.iter())
+            .map(|(phase, recent)| {
+                let total: Duration = recent.iter().sum();
+                (*phase, total / recent.len().max(1) as u32)
+            })
+            .collect()
+    }
+
+    /// Every span kept as JSON in the Chrome trace event format, for chrome://tracing or Perfetto
+    pub fn chrome_trace(&self) -> String {
+        let events: Vec<String> = self
+            .spans
+            .iter()
+            .map(|(phase, start, length)| {
+                // rendering goes on its own track
+                let track = match phase {
+                    Phase::MeshBuild | Phase::Draw => 2,
+                    _ => 1,
+                };
+                // in microseconds
+                format!(
+                    "{{\"name\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}}",
+                    phase.name(),
+                    start.as_secs_f64() * 1e6,
+                    length.as_secs_f64() * 1e6,
+                    track
+                )
+            })
+            .collect();
+        format!("[\n{}\n]\n", events.join(",\n"))
+    }
+
+    pub fn write_chrome_trace(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.chrome_trace())
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+#[test]
+fn ticks_are_broken_into_phases() {
+    use crate::world::World;
+
+    let mut world = World::builder().seed(3).build();
+    world.profiler.enabled = true;
+    world.profiler.trace = true;
+    for _ in 0..20 {
+        world.step();
+    }
+
+    let averages = world.profiler.averages();
+    let took = |p: Phase| averages.iter().find(|(q, _)| *q == p).unwrap().1;
+    assert!(took(Phase::Sensing) > Duration::default());
+    assert!(took(Phase::Movement) > Duration::default());
+    assert_eq!(took(Phase::Draw), Duration::default());
+    let trace = world.profiler.chrome_trace();
+    assert_eq!(trace.matches("\"name\":\"scent decay\"").count(), 20);
+    assert_eq!(trace.matches("\"name\":\"food growth\"").count(), 20);
+
+    // off by default, and then nothing is kept
+    let mut world = World::builder().seed(3).build();
+    world.step();
+    assert!(world.profiler.chrome_trace().trim() == "[\n\n]");
+}
@@ -0,0 +1,331 @@
+use numpy::ndarray::Array2;
+use numpy::{Element, IntoPyArray, PyArray1, PyArray2};
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::brood::Stage;
+use crate::colony::Colony;
+use crate::combat::Territory;
+use crate::config::Config;
+use crate::food::FoodKind;
+use crate::pheromone::{Pheromone, PheromoneKind, Response};
+use crate::scent::Scent;
+use crate::wind::Wind;
+use crate::world::World;
+
+/// A simulation which can be driven from python
+#[pyclass(unsendable, name = "World")]
+struct PyWorld {
+    world: World,
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    #[pyo3(signature = (config=None, seed=None))]
+    fn new(config: Option<Config>, seed: Option<u64>) -> PyResult<PyWorld> {
+        let config = config.unwrap_or_default();
+        config.validate().map_err(PyValueError::new_err)?;
+        let mut builder = World::builder().config(config);
+        if let Some(seed) = seed {
+            builder = builder.seed(seed);
+        }
+        Ok(PyWorld {
+            world: builder.build(),
+        })
+    }
+
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.world.tick
+    }
+
+    /// Food a colony has stored at home as a dict of kind to amount
+    #[pyo3(signature = (colony=0))]
+    fn stores<'py>(&self, py: Python<'py>, colony: usize) -> PyResult<Bound<'py, PyDict>> {
+        let out = PyDict::new(py);
+        for (kind, amount) in self.colony(colony)?.stores.iter() {
+            out.set_item(food_name(kind), *amount)?;
+        }
+        Ok(out)
+    }
+
+    /// Ticks a colony's queen has left to live, None once she has died
+    #[pyo3(signature = (colony=0))]
+    fn queen_life(&self, colony: usize) -> PyResult<Option<u32>> {
+        Ok(self.colony(colony)?.queen.as_ref().map(|q| q.life))
+    }
+
+    #[getter]
+    fn config(&self) -> Config {
+        self.world.config.clone()
+    }
+
+    /// Advance the simulation by `ticks` ticks, raises RuntimeError if `check_invariants` finds
+    /// something wrong
+    #[pyo3(signature = (ticks=1))]
+    fn step(&mut self, ticks: u64) -> PyResult<()> {
+        for _ in 0..ticks {
+            self.world.step();
+            if let Some(v) = &self.world.violation {
+                return Err(PyRuntimeError::new_err(v.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Ant state as a dict of arrays, one row per living ant
+    fn ants<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let ants = &self.world.ants;
+        let positions: Vec<f32> = ants
+            .iter()
+            .flat_map(|a| vec![a.position.x, a.position.y])
+            .collect();
+
+        let out = PyDict::new(py);
+        out.set_item("id", PyArray1::from_iter(py, ants.iter().map(|a| a.id)))?;
+        out.set_item("position", to_array2(py, positions, 2)?)?;
+        out.set_item(
+            "direction",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.direction)),
+        )?;
+        out.set_item(
+            "has_food",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.has_food)),
+        )?;
+        out.set_item("life", PyArray1::from_iter(py, ants.iter().map(|a| a.life)))?;
+        out.set_item(
+            "colony",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.colony as u32)),
+        )?;
+        out.set_item(
+            "caste",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.caste as u8)),
+        )?;
+        out.set_item(
+            "health",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.health)),
+        )?;
+        out.set_item(
+            "bridging",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.bridging)),
+        )?;
+        out.set_item(
+            "food_kind",
+            PyArray1::from_iter(py, ants.iter().map(|a| a.food_kind as u8)),
+        )?;
+        Ok(out)
+    }
+
+    /// Scents as an (n, 4) array of x, y, direction, life
+    ///
+    /// `kind` is "home", "alarm", "repellent", "exploration", "necromone" or the name of a food
+    /// trail, "sugar" or "protein", necromone is shared by every colony
+    #[pyo3(signature = (kind, colony=0))]
+    fn scents<'py>(
+        &self,
+        py: Python<'py>,
+        kind: &str,
+        colony: usize,
+    ) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let c = self.colony(colony)?;
+        let pheromone = PheromoneKind::ALL
+            .iter()
+            .find(|k| **k != PheromoneKind::Food && k.name() == kind);
+        let scents: &Vec<Scent> = match (kind, pheromone) {
+            ("necromone", _) => &self.world.necromone,
+            (_, Some(pheromone)) => c.scents(*pheromone, FoodKind::Sugar),
+            (food, None) => &c.food_scents[parse_food(food)?],
+        };
+        let values = scents
+            .iter()
+            .flat_map(|s| vec![s.position.x, s.position.y, s.direction, s.life as f32])
+            .collect();
+        to_array2(py, values, 4)
+    }
+
+    /// Food of the given kind, "sugar" or "protein", as an (n, 2) array of x, y
+    fn food<'py>(&self, py: Python<'py>, kind: &str) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let values = self.world.food_positions[parse_food(kind)?]
+            .iter()
+            .flat_map(|f| vec![f.x, f.y])
+            .collect();
+        to_array2(py, values, 2)
+    }
+
+    /// Corpses lying on the ground as an (n, 3) array of x, y, age
+    fn corpses<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let values = self
+            .world
+            .corpses
+            .iter()
+            .flat_map(|c| vec![c.position.x, c.position.y, c.age as f32])
+            .collect();
+        to_array2(py, values, 3)
+    }
+
+    /// Ponds as an (n, 3) array of x, y, radius
+    fn water<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let values = self
+            .world
+            .water
+            .iter()
+            .flat_map(|p| vec![p.centre.x, p.centre.y, p.radius])
+            .collect();
+        to_array2(py, values, 3)
+    }
+
+    #[pyo3(signature = (colony=0))]
+    fn midden(&self, colony: usize) -> PyResult<(f32, f32)> {
+        let m = &self.colony(colony)?.midden_position;
+        Ok((m.x, m.y))
+    }
+
+    /// Which colony holds each cell of the map as a (rows, columns) array, -1 where nobody does
+    #[pyo3(signature = (cell_size=10.0))]
+    fn territory<'py>(
+        &self,
+        py: Python<'py>,
+        cell_size: f32,
+    ) -> PyResult<Bound<'py, PyArray2<i32>>> {
+        if cell_size <= 0.0 {
+            return Err(PyValueError::new_err("cell_size must be positive"));
+        }
+        let t = Territory::new(&self.world, cell_size);
+        let cells = t.cells.iter().map(|c| c.map_or(-1, |c| c as i32)).collect();
+        to_array2(py, cells, t.width)
+    }
+
+    /// Current counts and running totals
+    fn metrics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let m = &self.world.metrics;
+        let out = PyDict::new(py);
+        out.set_item("tick", self.world.tick)?;
+        out.set_item("ants", self.world.ants.len())?;
+        out.set_item("colonies", self.world.colonies.len())?;
+        // colony figures are totals across every colony
+        let colonies = &self.world.colonies;
+        let total = |f: &dyn Fn(&Colony) -> usize| colonies.iter().map(f).sum::<usize>();
+        for kind in PheromoneKind::ALL
+            .iter()
+            .filter(|k| **k != PheromoneKind::Food)
+        {
+            out.set_item(
+                format!("{}_scents", kind.name()),
+                total(&|c| c.scents(*kind, FoodKind::Sugar).len()),
+            )?;
+        }
+        for kind in FoodKind::ALL.iter() {
+            let name = food_name(*kind);
+            out.set_item(
+                format!("{}_stored", name),
+                total(&|c| c.stores[*kind] as usize),
+            )?;
+            out.set_item(
+                format!("{}_remaining", name),
+                self.world.food_positions[*kind].len(),
+            )?;
+            out.set_item(
+                format!("{}_scents", name),
+                total(&|c| c.food_scents[*kind].len()),
+            )?;
+        }
+        out.set_item("food_collected", m.food_collected)?;
+        out.set_item("food_delivered", m.food_delivered)?;
+        out.set_item("ants_spawned", m.ants_spawned)?;
+        out.set_item("ants_died", m.ants_died)?;
+        out.set_item("ants_killed", m.ants_killed)?;
+        out.set_item("ants_drowned", m.ants_drowned)?;
+        out.set_item("food_grown", m.food_grown)?;
+        out.set_item("food_eaten", m.food_eaten)?;
+        out.set_item("food_lost", m.food_lost)?;
+        for (name, stage) in [
+            ("eggs", Stage::Egg),
+            ("larvae", Stage::Larva),
+            ("pupae", Stage::Pupa),
+        ]
+        .iter()
+        {
+            let count = total(&|c| c.brood.iter().filter(|b| b.stage == *stage).count());
+            out.set_item(*name, count)?;
+        }
+        out.set_item("eggs_laid", m.eggs_laid)?;
+        out.set_item("brood_died", m.brood_died)?;
+        out.set_item("corpses", self.world.corpses.len())?;
+        out.set_item("corpses_buried", m.corpses_buried)?;
+        out.set_item("corpses_decayed", m.corpses_decayed)?;
+        Ok(out)
+    }
+}
+
+impl PyWorld {
+    fn colony(&self, colony: usize) -> PyResult<&Colony> {
+        self.world
+            .colonies
+            .get(colony)
+            .ok_or_else(|| PyIndexError::new_err(format!("no colony {}", colony)))
+    }
+}
+
+#[pymethods]
+impl Config {
+    #[new]
+    fn py_new() -> Config {
+        Config::default()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+#[pymethods]
+impl Pheromone {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+#[pymethods]
+impl Wind {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+fn food_name(kind: FoodKind) -> &'static str {
+    match kind {
+        FoodKind::Sugar => "sugar",
+        FoodKind::Protein => "protein",
+    }
+}
+
+fn parse_food(name: &str) -> PyResult<FoodKind> {
+    FoodKind::ALL
+        .iter()
+        .find(|k| food_name(**k) == name)
+        .cloned()
+        .ok_or_else(|| PyValueError::new_err(format!("unknown food kind {}", name)))
+}
+
+fn to_array2<T: Element>(
+    py: Python<'_>,
+    values: Vec<T>,
+    columns: usize,
+) -> PyResult<Bound<'_, PyArray2<T>>> {
+    let rows = values.len() / columns;
+    let array = Array2::from_shape_vec((rows, columns), values)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(array.into_pyarray(py))
+}
+
+#[pymodule]
+fn ants(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWorld>()?;
+    m.add_class::<Config>()?;
+    m.add_class::<Pheromone>()?;
+    m.add_class::<Response>()?;
+    m.add_class::<Wind>()?;
+    Ok(())
+}
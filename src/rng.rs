@@ -0,0 +1,28 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseed the simulation's random number generator so that a run can be repeated
+pub fn seed(seed: u64) {
+    RNG.with(|r| *r.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Run `f` with the simulation's random number generator
+///
+/// All randomness in the simulation should come through here rather than `thread_rng`
+pub fn with<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|r| f(&mut r.borrow_mut()))
+}
+
+/// A copy of the generator as it is now, so that it can be put back with `restore`
+pub fn state() -> StdRng {
+    RNG.with(|r| r.borrow().clone())
+}
+
+pub fn restore(state: StdRng) {
+    RNG.with(|r| *r.borrow_mut() = state);
+}
@@ -0,0 +1,449 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+use tracing::info;
+
+use crate::colony::Colony;
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::food::FoodKind;
+use crate::invariants::Violation;
+use crate::wall::Wall;
+use crate::water::Pond;
+use crate::world::{World, X_SIZE, Y_SIZE};
+
+/// Names which objectives can check, see `metric`
+pub const METRICS: [&str; 21] = [
+    "tick",
+    "ants",
+    "queens",
+    "food_collected",
+    "food_delivered",
+    "ants_spawned",
+    "ants_died",
+    "ants_killed",
+    "ants_drowned",
+    "eggs_laid",
+    "brood_died",
+    "food_grown",
+    "food_eaten",
+    "food_lost",
+    "corpses_buried",
+    "corpses_decayed",
+    "sugar_stored",
+    "protein_stored",
+    "sugar_remaining",
+    "protein_remaining",
+    "brood",
+];
+
+/// A starting world and what should have happened in it by when, loaded from a TOML file
+///
+/// ```toml
+/// name = "walled garden"
+/// seed = 3
+/// ticks = 5000
+/// nests = [[250, 250]]
+/// walls = [[300, 150, 300, 350]]
+///
+/// [config]
+/// food_clusters = 0
+///
+/// [[food]]
+/// kind = "sugar"
+/// x = 400
+/// y = 250
+/// size = 40
+///
+/// [[objectives]]
+/// metric = "food_delivered"
+/// at_least = 20
+/// by = 5000
+///
+/// [[objectives]]
+/// survives = 5000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub seed: u64,
+    /// ticks to run for, the run carries on if an objective is checked later than this
+    #[serde(default)]
+    pub ticks: u64,
+    /// anything left out keeps its default
+    #[serde(default)]
+    pub config: Config,
+    /// x, y of each nest, in place of the nest sites the config would pick
+    #[serde(default)]
+    pub nests: Vec<[f32; 2]>,
+    /// food placed on top of any clusters the config scatters
+    #[serde(default)]
+    pub food: Vec<FoodCluster>,
+    /// x, y of each end
+    #[serde(default)]
+    pub walls: Vec<[f32; 4]>,
+    /// x, y, radius, on top of any ponds the config scatters
+    #[serde(default)]
+    pub ponds: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodCluster {
+    pub kind: FoodKind,
+    pub x: f32,
+    pub y: f32,
+    pub size: u32,
+}
+
+/// Something which has to hold for the scenario to pass, either a metric within bounds on a
+/// given tick or the colonies lasting until a given tick
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Objective {
+    pub metric: Option<String>,
+    pub at_least: Option<f64>,
+    pub at_most: Option<f64>,
+    /// tick the metric is checked on, the end of the run if not given
+    pub by: Option<u64>,
+    /// the colonies must not have collapsed by this tick
+    pub survives: Option<u64>,
+}
+
+impl Objective {
+    /// Tick this is checked on, given how long the run is
+    fn tick(&self, ticks: u64) -> u64 {
+        self.survives.or(self.by).unwrap_or(ticks)
+    }
+
+    /// Whether it holds for the world as it stands, with the value it looked at
+    fn check(&self, world: &World) -> (bool, f64) {
+        if self.survives.is_some() {
+            return (!world.collapsed(), world.ants.len() as f64);
+        }
+        let value = self
+            .metric
+            .as_ref()
+            .and_then(|m| metric(world, m))
+            .unwrap_or(f64::NAN);
+        let passed = self.at_least.is_none_or(|min| value >= min)
+            && self.at_most.is_none_or(|max| value <= max);
+        (passed, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match (&self.metric, self.survives) {
+            (Some(_), Some(_)) => Err("an objective can't have both a metric and survives".into()),
+            (None, None) => Err("an objective needs either a metric or survives".into()),
+            (None, Some(_))
+                if self.by.is_some() || self.at_least.is_some() || self.at_most.is_some() =>
+            {
+                Err("survives can't be given by, at_least or at_most".into())
+            }
+            (Some(m), None) if !METRICS.contains(&m.as_str()) => {
+                Err(format!("unknown metric {:?}", m))
+            }
+            (Some(m), None) if self.at_least.is_none() && self.at_most.is_none() => {
+                Err(format!("{} needs at_least or at_most", m))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(tick) = self.survives {
+            return write!(f, "survives {} ticks", tick);
+        }
+        write!(f, "{}", self.metric.as_deref().unwrap_or("?"))?;
+        if let Some(min) = self.at_least {
+            write!(f, " >= {}", min)?;
+        }
+        if let Some(max) = self.at_most {
+            write!(f, " <= {}", max)?;
+        }
+        match self.by {
+            Some(tick) => write!(f, " by tick {}", tick),
+            None => write!(f, " at the end"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "{}", e),
+            ScenarioError::Parse(e) => write!(f, "{}", e),
+            ScenarioError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        let text = fs::read_to_string(path).map_err(ScenarioError::Io)?;
+        let scenario = Scenario::parse(&text)?;
+        info!(
+            path = %path.display(),
+            name = %scenario.name,
+            objectives = scenario.objectives.len(),
+            "scenario loaded"
+        );
+        Ok(scenario)
+    }
+
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let scenario: Scenario = toml::from_str(text).map_err(ScenarioError::Parse)?;
+        for o in scenario.objectives.iter() {
+            o.validate().map_err(ScenarioError::Invalid)?;
+        }
+        scenario
+            .config()
+            .validate()
+            .map_err(ScenarioError::Invalid)?;
+        Ok(scenario)
+    }
+
+    /// The config the world is built with, one colony for each nest if any are given
+    pub fn config(&self) -> Config {
+        let mut config = self.config.clone();
+        if !self.nests.is_empty() {
+            config.colonies = self.nests.len() as u32;
+        }
+        config
+    }
+
+    /// Ticks the run lasts, long enough to check every objective
+    pub fn length(&self) -> u64 {
+        self.objectives
+            .iter()
+            .map(|o| o.tick(self.ticks))
+            .fold(self.ticks, u64::max)
+    }
+
+    /// The world as the scenario starts it off
+    pub fn build(&self) -> World {
+        let mut world = World::builder()
+            .seed(self.seed)
+            .config(self.config())
+            .empty()
+            .build();
+        if !self.nests.is_empty() {
+            let centre = Coordinate::new(X_SIZE / 2.0, Y_SIZE / 2.0);
+            // the queens are the first ants, as they would have been
+            world.colonies = self
+                .nests
+                .iter()
+                .enumerate()
+                .map(|(i, [x, y])| {
+                    let home = Coordinate::new(*x, *y);
+                    let facing = if home == centre {
+                        0.0
+                    } else {
+                        centre.direction(&home)
+                    };
+                    Colony::new(i, home, facing, i as u64, &world.config)
+                })
+                .collect();
+        }
+        world.walls = self
+            .walls
+            .iter()
+            .map(|[x1, y1, x2, y2]| Wall::new(Coordinate::new(*x1, *y1), Coordinate::new(*x2, *y2)))
+            .collect();
+        world.populate();
+        for [x, y, radius] in self.ponds.iter() {
+            world.water.push(Pond {
+                centre: Coordinate::new(*x, *y),
+                radius: *radius,
+            });
+        }
+        for f in self.food.iter() {
+            world.place_cluster(f.kind, f.x, f.y, f.size);
+        }
+        world
+    }
+
+    /// Play the scenario out and check each objective on its tick
+    pub fn run(&self) -> Outcome {
+        let mut world = self.build();
+        let length = self.length();
+        let mut results: Vec<Option<(bool, f64)>> = vec![None; self.objectives.len()];
+        loop {
+            for (o, r) in self.objectives.iter().zip(results.iter_mut()) {
+                if r.is_none() && o.tick(self.ticks) == world.tick {
+                    *r = Some(o.check(&world));
+                }
+            }
+            if world.tick >= length || world.violation.is_some() {
+                break;
+            }
+            world.step();
+        }
+        info!(name = %self.name, ticks = world.tick, "scenario finished");
+        Outcome {
+            name: self.name.clone(),
+            ticks: world.tick,
+            violation: world.violation.take(),
+            results: self
+                .objectives
+                .iter()
+                .cloned()
+                .zip(results.into_iter().map(|r| r.unwrap_or((false, f64::NAN))))
+                .map(|(o, (passed, value))| (o, passed, value))
+                .collect(),
+        }
+    }
+}
+
+/// How a scenario went, each objective along with whether it passed and the value it saw
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub name: String,
+    pub ticks: u64,
+    /// what stopped the run early when checking invariants
+    pub violation: Option<Violation>,
+    pub results: Vec<(Objective, bool, f64)>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.violation.is_none() && self.results.iter().all(|(_, passed, _)| *passed)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verdict = if self.passed() { "PASS" } else { "FAIL" };
+        write!(f, "{} {} ({} ticks)", verdict, self.name, self.ticks)?;
+        for (o, passed, value) in self.results.iter() {
+            let mark = if *passed { "ok" } else { "FAILED" };
+            write!(f, "\n    {:<6} {}", mark, o)?;
+            if o.survives.is_some() {
+                write!(f, " ({} ants left)", value)?;
+            } else {
+                write!(f, " (was {})", value)?;
+            }
+        }
+        if let Some(v) = &self.violation {
+            write!(f, "\n    {}", v)?;
+        }
+        Ok(())
+    }
+}
+
+/// Look up a figure about the world by name, totalled across the colonies, None if there is no
+/// such metric
+pub fn metric(world: &World, name: &str) -> Option<f64> {
+    let m = &world.metrics;
+    let colonies = &world.colonies;
+    let stored = |kind: FoodKind| colonies.iter().map(|c| c.stores[kind]).sum::<u32>();
+    let value = match name {
+        "tick" => world.tick as f64,
+        "ants" => world.ants.len() as f64,
+        "queens" => colonies.iter().filter(|c| c.queen.is_some()).count() as f64,
+        "food_collected" => m.food_collected as f64,
+        "food_delivered" => m.food_delivered as f64,
+        "ants_spawned" => m.ants_spawned as f64,
+        "ants_died" => m.ants_died as f64,
+        "ants_killed" => m.ants_killed as f64,
+        "ants_drowned" => m.ants_drowned as f64,
+        "eggs_laid" => m.eggs_laid as f64,
+        "brood_died" => m.brood_died as f64,
+        "food_grown" => m.food_grown as f64,
+        "food_eaten" => m.food_eaten as f64,
+        "food_lost" => m.food_lost as f64,
+        "corpses_buried" => m.corpses_buried as f64,
+        "corpses_decayed" => m.corpses_decayed as f64,
+        "sugar_stored" => stored(FoodKind::Sugar) as f64,
+        "protein_stored" => stored(FoodKind::Protein) as f64,
+        "sugar_remaining" => world.food_positions.sugar.len() as f64,
+        "protein_remaining" => world.food_positions.protein.len() as f64,
+        "brood" => colonies.iter().map(|c| c.brood.len()).sum::<usize>() as f64,
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[test]
+fn scenarios_check_their_objectives() {
+    let scenario = Scenario::parse(
+        r#"
+        name = "boxed in"
+        seed = 2
+        ticks = 200
+        nests = [[250, 250]]
+        # the nest is walled in on every side
+        walls = [
+            [200, 200, 300, 200],
+            [300, 200, 300, 300],
+            [300, 300, 200, 300],
+            [200, 300, 200, 200],
+        ]
+
+        [config]
+        food_clusters = 0
+        cluster_spawn_chance = 0.0
+
+        [[food]]
+        kind = "sugar"
+        x = 400
+        y = 250
+        size = 20
+
+        [[objectives]]
+        metric = "food_collected"
+        at_most = 0
+
+        [[objectives]]
+        metric = "food_collected"
+        at_least = 1
+        by = 100
+
+        [[objectives]]
+        survives = 200
+        "#,
+    )
+    .unwrap();
+    let world = scenario.build();
+    assert_eq!(
+        world.colonies[0].home_position,
+        Coordinate::new(250.0, 250.0)
+    );
+    assert_eq!(world.walls.len(), 4);
+
+    let outcome = scenario.run();
+    assert_eq!(outcome.ticks, 200);
+    let passed: Vec<bool> = outcome.results.iter().map(|(_, p, _)| *p).collect();
+    assert_eq!(passed, vec![true, false, true]);
+    assert!(!outcome.passed());
+    // no ant got out
+    let mut world = scenario.build();
+    for _ in 0..200 {
+        world.step();
+    }
+    let inside = |p: &Coordinate| p.x > 200.0 && p.x < 300.0 && p.y > 200.0 && p.y < 300.0;
+    assert!(world.ants.iter().all(|a| inside(&a.position)));
+
+    assert!(
+        Scenario::parse("name = \"x\"\n[[objectives]]\nmetric = \"nope\"\nat_least = 1").is_err()
+    );
+    assert!(Scenario::parse("name = \"x\"\n[[objectives]]\nsurvives = 10\nat_least = 1").is_err());
+}
@@ -1,14 +1,13 @@
-#![feature(drain_filter)]
-#![feature(destructuring_assignment)]
-
 use crate::coordinate::Coordinate;
 use crate::navigable::Navigable;
+use crate::rng;
 use rand::prelude::*;
 use rand_distr::Normal;
 
 pub const SCENT_LIFE: u32 = 500;
 const RANDOMNESS: f32 = 15.0;
 
+#[derive(Clone)]
 pub struct Scent {
     pub position: Coordinate,
     pub direction: f32,
@@ -51,7 +50,7 @@ impl Navigable for Vec<Scent> {
             /*
             // we were unable to find a position, so we need to make one up
             let distribution = Normal::new(dir, RANDOMNESS).unwrap();
-            let mut direction = distribution.sample(&mut rand::thread_rng());
+            let mut direction = rng::with(|r| distribution.sample(r));
             // match to a valid direction
             while direction > 359.9 {
                 direction -= 359.9;
@@ -88,7 +87,7 @@ impl Navigable for Vec<Scent> {
         if let None = final_dir {
             // we were unable to find a position, so we need to make one up
             let distribution = Normal::new(dir, RANDOMNESS).unwrap();
-            let mut direction = distribution.sample(&mut rand::thread_rng());
+            let mut direction = rng::with(|r| distribution.sample(r));
             // match to a valid direction
             while direction > 359.9 {
                 direction -= 359.9;
@@ -0,0 +1,426 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+use tracing::{debug, info, warn};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::config::Config;
+use crate::scenario::{self, FoodCluster};
+use crate::world::World;
+
+/// How long a client waits on the simulation to answer before giving up
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often a paused headless run checks for requests
+const PAUSED_POLL: Duration = Duration::from_millis(20);
+/// Frames queued for a streaming client before newer ones are dropped, so one which has stalled
+/// can't use up all the memory
+const STREAM_BACKLOG: usize = 8;
+/// Config fields which only take effect as the world is built, changing them later would leave
+/// the world at odds with its config
+const BUILD_ONLY: [&str; 13] = [
+    "colonies",
+    "colony_spacing",
+    "midden_distance",
+    "starting_ants",
+    "starting_sugar",
+    "starting_protein",
+    "food_clusters",
+    "protein_clusters",
+    "hills",
+    "elevation",
+    "ponds",
+    "pond_radius",
+    "hex_size",
+];
+
+/// One sender per client streaming ants, dropped once the client goes away
+type Streams = Arc<Mutex<Vec<SyncSender<String>>>>;
+
+/// A request waiting for the simulation to answer it
+struct Call {
+    method: Method,
+    path: String,
+    body: String,
+    reply: Sender<(u16, Value)>,
+}
+
+/// A local HTTP server for watching and steering a running simulation
+///
+/// Requests are handled on their own threads but answered by whatever owns the world calling
+/// `serve`, so the world never leaves its thread
+///
+/// - `GET /state` the tick, every ant and every colony
+/// - `GET /metrics` everything `scenario::metric` knows about
+/// - `GET /config`, `POST /config` with any fields to change as a JSON object, those only used
+///   to build the world are left alone
+/// - `POST /pause`, `POST /resume`
+/// - `POST /food` with `{"kind": "sugar", "x": 100, "y": 100, "size": 20}`, it won't grow back
+/// - `GET /stream` a WebSocket sent `{"tick": 1, "ants": [[id, x, y], ...]}` every tick
+pub struct Server {
+    pub addr: SocketAddr,
+    calls: Receiver<Call>,
+    streams: Streams,
+}
+
+impl Server {
+    /// Start listening on `addr`, e.g. "127.0.0.1:8080"
+    ///
+    /// Nothing checks who is asking, so only loopback addresses are allowed unless `remote` is set
+    pub fn start(addr: &str, remote: bool) -> Result<Server, Box<dyn Error + Send + Sync>> {
+        let http = tiny_http::Server::http(addr)?;
+        let addr = http
+            .server_addr()
+            .to_ip()
+            .ok_or("the server has to listen on an ip address")?;
+        if !remote && !addr.ip().is_loopback() {
+            return Err(format!("{} can be reached from other machines", addr).into());
+        }
+        let (calls, received) = channel();
+        let streams = Arc::new(Mutex::new(vec![]));
+        let clients = streams.clone();
+        thread::spawn(move || {
+            for request in http.incoming_requests() {
+                let calls = calls.clone();
+                let clients = clients.clone();
+                thread::spawn(move || handle(request, calls, clients));
+            }
+        });
+        info!(%addr, "control server listening");
+        Ok(Server {
+            addr,
+            calls: received,
+            streams,
+        })
+    }
+
+    /// Answer every request which has come in since the last call, should be called once a
+    /// tick or frame by whatever owns the world
+    pub fn serve(&self, world: &mut World, paused: &mut bool) {
+        while let Ok(call) = self.calls.try_recv() {
+            let reply = answer(&call, world, paused);
+            debug!(method = %call.method, path = %call.path, status = reply.0, "request answered");
+            // the client may have given up waiting
+            let _ = call.reply.send(reply);
+        }
+    }
+
+    /// Like `serve` but carries on answering for as long as a client keeps the simulation
+    /// paused, for loops which would otherwise run flat out
+    pub fn serve_while_paused(&self, world: &mut World, paused: &mut bool) {
+        self.serve(world, paused);
+        while *paused {
+            thread::sleep(PAUSED_POLL);
+            self.serve(world, paused);
+        }
+    }
+
+    /// Send where every ant is to each client streaming them, should be called after each tick
+    pub fn broadcast(&self, world: &World) {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.is_empty() {
+            return;
+        }
+        let ants: Vec<Value> = world
+            .ants
+            .iter()
+            .map(|a| json!([a.id, a.position.x, a.position.y]))
+            .collect();
+        let frame = json!({ "tick": world.tick, "ants": ants }).to_string();
+        // a client which has fallen behind misses frames rather than queueing them up
+        streams.retain(|s| {
+            !matches!(
+                s.try_send(frame.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+    }
+}
+
+/// Pass a request on to the simulation and send back its answer, or hand the connection over to
+/// a WebSocket if it asks to stream
+fn handle(mut request: Request, calls: Sender<Call>, streams: Streams) {
+    if request.url() == "/stream" {
+        stream(request, streams);
+        return;
+    }
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        respond(request, 400, json!({ "error": e.to_string() }));
+        return;
+    }
+    let (reply, answer) = channel();
+    let call = Call {
+        method: request.method().clone(),
+        path: request.url().to_string(),
+        body,
+        reply,
+    };
+    let (status, body) = match calls
+        .send(call)
+        .ok()
+        .and_then(|_| answer.recv_timeout(REPLY_TIMEOUT).ok())
+    {
+        Some(answer) => answer,
+        None => (503, json!({ "error": "the simulation isn't answering" })),
+    };
+    respond(request, status, body);
+}
+
+fn respond(request: Request, status: u16, body: Value) {
+    // SAFE: both are plain ascii
+    let json = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(json);
+    if let Err(e) = request.respond(response) {
+        warn!("unable to answer a request: {}", e);
+    }
+}
+
+/// Upgrade the connection to a WebSocket and send it every frame `broadcast` puts out
+fn stream(request: Request, streams: Streams) {
+    let key = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Sec-WebSocket-Key"))
+        .map(|h| derive_accept_key(h.value.as_bytes()));
+    let key = match key {
+        Some(key) => key,
+        None => {
+            respond(request, 400, json!({ "error": "/stream is a WebSocket" }));
+            return;
+        }
+    };
+    let headers = [
+        ("Upgrade", "websocket"),
+        ("Connection", "Upgrade"),
+        ("Sec-WebSocket-Accept", key.as_str()),
+    ];
+    let mut response = Response::empty(StatusCode(101));
+    for (field, value) in headers.iter() {
+        // SAFE: all ascii
+        response.add_header(Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap());
+    }
+    let socket = request.upgrade("websocket", response);
+    let mut socket = WebSocket::from_raw_socket(socket, Role::Server, None);
+
+    let (frames, received) = sync_channel(STREAM_BACKLOG);
+    streams.lock().unwrap().push(frames);
+    debug!("client streaming ants");
+    for frame in received {
+        if socket.send(Message::Text(frame)).is_err() {
+            break;
+        }
+    }
+    debug!("client stopped streaming ants");
+}
+
+/// Work out the answer to a request against the world
+fn answer(call: &Call, world: &mut World, paused: &mut bool) -> (u16, Value) {
+    let bad = |e: &dyn std::fmt::Display| (400, json!({ "error": e.to_string() }));
+    match (&call.method, call.path.as_str()) {
+        (Method::Get, "/state") => (200, state(world, *paused)),
+        (Method::Get, "/metrics") => {
+            let metrics: serde_json::Map<String, Value> = scenario::METRICS
+                .iter()
+                .filter_map(|m| scenario::metric(world, m).map(|v| (m.to_string(), json!(v))))
+                .collect();
+            (200, Value::Object(metrics))
+        }
+        (Method::Get, "/config") => (200, json!(world.config)),
+        (Method::Post, "/config") => match change_config(world, &call.body) {
+            Ok(()) => {
+                info!(tick = world.tick, "config changed");
+                (200, json!(world.config))
+            }
+            Err(e) => bad(&e),
+        },
+        (Method::Post, "/pause") => {
+            *paused = true;
+            (200, json!({ "paused": true }))
+        }
+        (Method::Post, "/resume") => {
+            *paused = false;
+            (200, json!({ "paused": false }))
+        }
+        (Method::Post, "/food") => match serde_json::from_str::<FoodCluster>(&call.body) {
+            Ok(f) => {
+                world.place_food(f.kind, f.x, f.y, f.size);
+                // it has appeared from nowhere as far as the colonies are concerned
+                world.metrics.food_grown += f.size as u64;
+                info!(tick = world.tick, kind = ?f.kind, x = f.x, y = f.y, size = f.size, "food placed");
+                (200, json!({ "placed": f.size }))
+            }
+            Err(e) => bad(&e),
+        },
+        _ => (
+            404,
+            json!({ "error": format!("no {} {}", call.method, call.path) }),
+        ),
+    }
+}
+
+/// Lay the JSON object `body` over the world's config, refusing anything the simulation can't
+/// take part way through a run
+fn change_config(world: &mut World, body: &str) -> Result<(), String> {
+    let changes: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let old = json!(world.config);
+    let mut new = old.clone();
+    merge(&mut new, changes, "")?;
+    if let Some(field) = BUILD_ONLY.iter().find(|f| new[**f] != old[**f]) {
+        return Err(format!("{} can only be set when the world is built", field));
+    }
+    let config: Config = serde_json::from_value(new).map_err(|e| e.to_string())?;
+    config.validate()?;
+    world.config = config;
+    Ok(())
+}
+
+/// Lay `changes` over `into`, only fields which are already there can be changed
+fn merge(into: &mut Value, changes: Value, path: &str) -> Result<(), String> {
+    match (into, changes) {
+        (Value::Object(into), Value::Object(changes)) => {
+            for (field, value) in changes {
+                let path = format!("{}{}", path, field);
+                match into.get_mut(&field) {
+                    Some(existing) => merge(existing, value, &format!("{}.", path))?,
+                    None => return Err(format!("no such config field {}", path)),
+                }
+            }
+            Ok(())
+        }
+        (into, changes) => {
+            *into = changes;
+            Ok(())
+        }
+    }
+}
+
+fn state(world: &World, paused: bool) -> Value {
+    let ants: Vec<Value> = world
+        .ants
+        .iter()
+        .map(|a| {
+            json!({
+                "id": a.id,
+                "colony": a.colony,
+                "x": a.position.x,
+                "y": a.position.y,
+                "direction": a.direction,
+                "has_food": a.has_food,
+                "life": a.life,
+            })
+        })
+        .collect();
+    let colonies: Vec<Value> = world
+        .colonies
+        .iter()
+        .map(|c| {
+            json!({
+                "id": c.id,
+                "x": c.home_position.x,
+                "y": c.home_position.y,
+                "sugar": c.stores.sugar,
+                "protein": c.stores.protein,
+                "brood": c.brood.len(),
+                "queen": c.queen.is_some(),
+            })
+        })
+        .collect();
+    json!({
+        "tick": world.tick,
+        "paused": paused,
+        "violation": world.violation.as_ref().map(|v| v.to_string()),
+        "ants": ants,
+        "colonies": colonies,
+        "food": {
+            "sugar": world.food_positions.sugar.len(),
+            "protein": world.food_positions.protein.len(),
+        },
+    })
+}
+
+#[test]
+fn clients_can_watch_and_steer() {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    assert!(Server::start("0.0.0.0:0", false).is_err());
+    let server = Server::start("127.0.0.1:0", false).unwrap();
+    let addr = server.addr;
+    let mut world = World::builder().seed(2).build();
+    let mut paused = false;
+
+    let client = thread::spawn(move || {
+        let call = |method: &str, path: &str, body: &str| {
+            let mut s = TcpStream::connect(addr).unwrap();
+            write!(
+                s,
+                "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                method,
+                path,
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut reply = String::new();
+            s.read_to_string(&mut reply).unwrap();
+            reply
+        };
+        let (mut ws, _) = tungstenite::client(
+            format!("ws://{}/stream", addr),
+            TcpStream::connect(addr).unwrap(),
+        )
+        .unwrap();
+        let paused = call("POST", "/pause", "");
+        let food = call(
+            "POST",
+            "/food",
+            r#"{"kind": "protein", "x": 50, "y": 50, "size": 7}"#,
+        );
+        let config = call("POST", "/config", r#"{"wind": {"speed": 2.0}}"#);
+        let unknown = call("POST", "/config", r#"{"no_such_thing": 1}"#);
+        let invalid = call("POST", "/config", r#"{"ant_life": 0}"#);
+        let built = call("POST", "/config", r#"{"colonies": 2}"#);
+        let state = call("GET", "/state", "");
+        let frame = ws.read().unwrap().into_text().unwrap();
+        (
+            paused,
+            food,
+            config,
+            [unknown, invalid, built],
+            state,
+            frame,
+        )
+    });
+    while !client.is_finished() {
+        server.serve(&mut world, &mut paused);
+        world.step();
+        server.broadcast(&world);
+        thread::sleep(Duration::from_millis(1));
+    }
+    let (paused_reply, food, config, refused, state, frame) = client.join().unwrap();
+
+    assert!(paused_reply.starts_with("HTTP/1.1 200"));
+    assert!(paused);
+    assert!(food.contains("\"placed\":7"));
+    assert_eq!(world.metrics.food_grown, 7);
+    assert!(config.starts_with("HTTP/1.1 200"));
+    assert_eq!(world.config.wind.speed, 2.0);
+    for reply in refused.iter() {
+        assert!(reply.starts_with("HTTP/1.1 400"), "{}", reply);
+    }
+    assert_eq!(world.config.ant_life, Config::default().ant_life);
+    assert_eq!(world.config.colonies, 1);
+    assert!(state.contains("\"paused\":true"));
+    let frame: Value = serde_json::from_str(&frame).unwrap();
+    assert!(!frame["ants"].as_array().unwrap().is_empty());
+}
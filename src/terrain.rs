@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use rand::prelude::*;
+
+use crate::coordinate::Coordinate;
+use crate::rng;
+use crate::scent::Scent;
+use crate::world::{X_SIZE, Y_SIZE};
+
+/// Samples across each side of a generated heightmap
+const GENERATED_SIZE: usize = 100;
+/// Distance either side of a point used to work out the slope there
+const SLOPE_STEP: f32 = 2.0;
+/// Slowest and fastest an ant can go on a slope, as a fraction of its speed on the flat
+const MIN_SLOPE_SPEED: f32 = 0.25;
+const MAX_SLOPE_SPEED: f32 = 2.0;
+
+/// How high the ground is across the map, stretched over the whole map whatever its size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    pub width: usize,
+    pub height: usize,
+    /// row major, 0 -> 1
+    pub heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Load a greyscale image, black is the lowest ground and white the highest
+    pub fn from_image(path: &Path) -> Result<Heightmap, image::ImageError> {
+        let img = image::open(path)?.to_luma();
+        Ok(Heightmap {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            heights: img.pixels().map(|p| p[0] as f32 / 255.0).collect(),
+        })
+    }
+
+    /// Rolling ground made of `hills` random bumps
+    pub fn generate(hills: u32) -> Heightmap {
+        let size = GENERATED_SIZE;
+        let bumps: Vec<(f32, f32, f32, f32)> = rng::with(|r| {
+            (0..hills)
+                .map(|_| {
+                    (
+                        r.gen::<f32>(),
+                        r.gen::<f32>(),
+                        0.05 + r.gen::<f32>() * 0.2,
+                        0.3 + r.gen::<f32>() * 0.7,
+                    )
+                })
+                .collect()
+        });
+        let mut heights: Vec<f32> = (0..size * size)
+            .map(|i| {
+                let x = (i % size) as f32 / (size - 1) as f32;
+                let y = (i / size) as f32 / (size - 1) as f32;
+                bumps
+                    .iter()
+                    .map(|(bx, by, radius, peak)| {
+                        let d2 = (x - bx).powi(2) + (y - by).powi(2);
+                        peak * (-d2 / (2.0 * radius * radius)).exp()
+                    })
+                    .sum()
+            })
+            .collect();
+        let highest = heights.iter().cloned().fold(0.0, f32::max);
+        if highest > 0.0 {
+            for h in heights.iter_mut() {
+                *h /= highest;
+            }
+        }
+        Heightmap {
+            width: size,
+            height: size,
+            heights,
+        }
+    }
+
+    /// Height at a point on the map, 0 -> 1, blended between the nearest samples
+    pub fn at(&self, pos: &Coordinate) -> f32 {
+        let x = (pos.x / X_SIZE).clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let y = (pos.y / Y_SIZE).clamp(0.0, 1.0) * (self.height - 1) as f32;
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let h = |x: usize, y: usize| self.heights[y * self.width + x];
+        let top = h(x0, y0) + (h(x1, y0) - h(x0, y0)) * fx;
+        let bottom = h(x0, y1) + (h(x1, y1) - h(x0, y1)) * fx;
+        top + (bottom - top) * fy
+    }
+}
+
+/// The ground ants walk over
+pub struct Terrain {
+    pub map: Heightmap,
+    /// height of the highest ground in the same units as the map
+    pub elevation: f32,
+}
+
+impl Terrain {
+    /// Height at a point in map units
+    pub fn height(&self, pos: &Coordinate) -> f32 {
+        self.map.at(pos) * self.elevation
+    }
+
+    /// How far an ant which walks `speed` on the flat gets heading `direction` from `pos`,
+    /// slower uphill and quicker downhill
+    pub fn speed(&self, pos: &Coordinate, direction: f32, speed: f32) -> f32 {
+        if speed <= 0.0 {
+            return speed;
+        }
+        let ahead = pos.traverse_direction(direction, speed);
+        let slope = (self.height(&ahead) - self.height(pos)) / speed;
+        speed * (1.0 - slope).clamp(MIN_SLOPE_SPEED, MAX_SLOPE_SPEED)
+    }
+
+    /// Which way is downhill at `pos` and how steep it is, as (direction, slope)
+    pub fn downhill(&self, pos: &Coordinate) -> (f32, f32) {
+        let dx = self.height(&Coordinate::new(pos.x + SLOPE_STEP, pos.y))
+            - self.height(&Coordinate::new(pos.x - SLOPE_STEP, pos.y));
+        let dy = self.height(&Coordinate::new(pos.x, pos.y + SLOPE_STEP))
+            - self.height(&Coordinate::new(pos.x, pos.y - SLOPE_STEP));
+        let slope = (dx * dx + dy * dy).sqrt() / (2.0 * SLOPE_STEP);
+        let direction = (-dy).atan2(-dx).to_degrees();
+        ((direction + 360.0) % 360.0, slope)
+    }
+
+    /// Let a scent run a little way downhill, so they gather in the hollows
+    pub fn pool(&self, scent: &mut Scent, rate: f32) {
+        let (direction, slope) = self.downhill(&scent.position);
+        if slope > 0.0 {
+            scent.position = scent
+                .position
+                .traverse_direction(direction, slope * rate)
+                .check_bounds(0.0, X_SIZE, 0.0, Y_SIZE);
+        }
+    }
+}
+
+#[test]
+fn ants_slow_down_uphill() {
+    // a ramp rising from west to east
+    let terrain = Terrain {
+        map: Heightmap {
+            width: 2,
+            height: 2,
+            heights: vec![0.0, 1.0, 0.0, 1.0],
+        },
+        elevation: 250.0,
+    };
+    let pos = Coordinate::new(250.0, 250.0);
+
+    assert!(terrain.speed(&pos, 0.0, 1.0) < 1.0);
+    assert!(terrain.speed(&pos, 180.0, 1.0) > 1.0);
+    assert!((terrain.speed(&pos, 90.0, 1.0) - 1.0).abs() < 0.01);
+    let (direction, _) = terrain.downhill(&pos);
+    assert!((direction - 180.0).abs() < 0.01);
+}
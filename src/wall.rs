@@ -0,0 +1,32 @@
+use crate::coordinate::Coordinate;
+
+/// A straight wall which ants can't walk through
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wall {
+    pub from: Coordinate,
+    pub to: Coordinate,
+}
+
+impl Wall {
+    pub fn new(from: Coordinate, to: Coordinate) -> Wall {
+        Wall { from, to }
+    }
+
+    /// True if walking straight from `a` to `b` goes through the wall
+    pub fn blocks(&self, a: &Coordinate, b: &Coordinate) -> bool {
+        // which side of the line through `p` and `q` that `r` is on
+        let side = |p: &Coordinate, q: &Coordinate, r: &Coordinate| {
+            (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x)
+        };
+        let d1 = side(&self.from, &self.to, a);
+        let d2 = side(&self.from, &self.to, b);
+        let d3 = side(a, b, &self.from);
+        let d4 = side(a, b, &self.to);
+        d1 * d2 < 0.0 && d3 * d4 < 0.0
+    }
+}
+
+/// True if any of the walls are in the way of walking from `a` to `b`
+pub fn blocked(walls: &Vec<Wall>, a: &Coordinate, b: &Coordinate) -> bool {
+    walls.iter().any(|w| w.blocks(a, b))
+}
@@ -0,0 +1,107 @@
+use rand::prelude::*;
+
+use crate::coordinate::Coordinate;
+use crate::rng;
+use crate::world::{HOME_SIZE, X_SIZE, Y_SIZE};
+
+/// How close nestmates have to be to help an ant into the water
+pub const SHORE_RANGE: f32 = 10.0;
+/// How far from an ant holding a bridge the water is safe to cross
+pub const BRIDGE_SPAN: f32 = 5.0;
+/// Tries at finding a spot for a pond away from the nests before giving up on it
+const POND_TRIES: u32 = 20;
+
+/// A round patch of water which ants drown in unless they cross together
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pond {
+    pub centre: Coordinate,
+    pub radius: f32,
+}
+
+impl Pond {
+    pub fn contains(&self, pos: &Coordinate) -> bool {
+        pos.dist(&self.centre) < self.radius
+    }
+}
+
+pub fn in_water(pos: &Coordinate, ponds: &Vec<Pond>) -> bool {
+    ponds.iter().any(|p| p.contains(pos))
+}
+
+/// True if an ant holding a bridge is close enough to `pos` to walk over
+pub fn bridged(pos: &Coordinate, bridges: &Vec<Coordinate>) -> bool {
+    bridges.iter().any(|b| b.dist(pos) < BRIDGE_SPAN)
+}
+
+/// Scatter `count` ponds about the map, keeping clear of the nests
+pub fn scatter(count: u32, radius: f32, nests: &Vec<Coordinate>) -> Vec<Pond> {
+    let mut ponds = vec![];
+    for _ in 0..count {
+        for _ in 0..POND_TRIES {
+            let centre =
+                rng::with(|r| Coordinate::new(r.gen::<f32>() * X_SIZE, r.gen::<f32>() * Y_SIZE));
+            if nests
+                .iter()
+                .all(|n| n.dist(&centre) > radius + HOME_SIZE * 2.0)
+            {
+                ponds.push(Pond { centre, radius });
+                break;
+            }
+        }
+    }
+    ponds
+}
+
+#[test]
+fn ants_only_cross_water_together() {
+    use crate::brain::Action;
+    use crate::config::Config;
+    use crate::world::World;
+    use std::collections::HashMap;
+
+    let mut world = World::builder()
+        .seed(1)
+        .config(Config {
+            starting_ants: 0,
+            starting_sugar: 0,
+            cluster_spawn_chance: 0.0,
+            bridge_ants: 3,
+            ..Config::default()
+        })
+        .empty()
+        .build();
+    world.water.push(Pond {
+        centre: Coordinate::new(400.0, 250.0),
+        radius: 20.0,
+    });
+    let walk_east: Vec<Action> = vec![Action::Turn(0.0), Action::Move];
+
+    // a lone ant at the shore walks in and drowns
+    world.spawn_ant(0);
+    world.ants[0].position = Coordinate::new(380.5, 250.0);
+    let overrides: HashMap<u64, Vec<Action>> = world
+        .ants
+        .iter()
+        .map(|a| (a.id, walk_east.clone()))
+        .collect();
+    world.step_with(&overrides);
+    assert!(world.ants.is_empty());
+    assert_eq!(world.metrics.ants_drowned, 1);
+
+    // three together make it, the first in holds a bridge for the others
+    for i in 0..3 {
+        world.spawn_ant(0);
+        world.ants[i].position = Coordinate::new(380.5 - i as f32 * 2.0, 250.0);
+    }
+    let overrides: HashMap<u64, Vec<Action>> = world
+        .ants
+        .iter()
+        .map(|a| (a.id, walk_east.clone()))
+        .collect();
+    for _ in 0..3 {
+        world.step_with(&overrides);
+    }
+    assert_eq!(world.ants.len(), 3);
+    assert_eq!(world.metrics.ants_drowned, 1);
+    assert!(world.ants.iter().any(|a| a.bridging > 0));
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::coordinate::Coordinate;
+use crate::scent::Scent;
+use crate::world::{X_SIZE, Y_SIZE};
+
+/// Air moving over the map, carrying scents with it and letting ants smell further upwind
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Wind {
+    /// how far scents are carried each tick, 0 means still air
+    pub speed: f32,
+    /// which way the wind blows towards, angle 0 -> 359
+    pub direction: f32,
+    /// how much gusts add to and take from the speed, 0 -> 1
+    pub gustiness: f32,
+    /// ticks from one gust to the next, 0 keeps the speed steady
+    pub gust_period: u32,
+    /// how far the direction swings either way across the map, 0 blows the same way everywhere
+    pub swirl: f32,
+    /// how far upwind an ant smells from for each unit of wind speed
+    pub sensing_bias: f32,
+}
+
+impl Wind {
+    /// Still air, nothing moves
+    pub fn calm() -> Wind {
+        Wind {
+            speed: 0.0,
+            direction: 0.0,
+            gustiness: 0.5,
+            gust_period: 300,
+            swirl: 0.0,
+            sensing_bias: 10.0,
+        }
+    }
+
+    /// Which way and how hard the wind is blowing at `pos` on the given tick, as
+    /// (direction, speed)
+    pub fn at(&self, pos: &Coordinate, tick: u64) -> (f32, f32) {
+        let mut speed = self.speed;
+        if self.gust_period > 0 {
+            let phase = (tick % self.gust_period as u64) as f32 / self.gust_period as f32;
+            speed *= 1.0 + self.gustiness * (phase * 2.0 * PI).sin();
+        }
+        let swirl =
+            self.swirl * (pos.x / X_SIZE * 2.0 * PI).sin() * (pos.y / Y_SIZE * 2.0 * PI).cos();
+        ((self.direction + swirl + 360.0) % 360.0, speed.max(0.0))
+    }
+
+    /// Carry a scent along with the wind for a tick
+    pub fn carry(&self, scent: &mut Scent, tick: u64) {
+        if self.speed <= 0.0 {
+            return;
+        }
+        let (direction, speed) = self.at(&scent.position, tick);
+        scent.position = scent
+            .position
+            .traverse_direction(direction, speed)
+            .check_bounds(0.0, X_SIZE, 0.0, Y_SIZE);
+    }
+
+    /// Where an ant at `pos` smells from, scents upwind are blown towards it so it picks them
+    /// up from further away
+    pub fn nose(&self, pos: &Coordinate, tick: u64) -> Coordinate {
+        if self.speed <= 0.0 {
+            return pos.clone();
+        }
+        let (direction, speed) = self.at(pos, tick);
+        let upwind = (direction + 180.0) % 360.0;
+        pos.traverse_direction(upwind, speed * self.sensing_bias)
+    }
+}
+
+impl Default for Wind {
+    fn default() -> Wind {
+        Wind::calm()
+    }
+}
+
+#[test]
+fn trails_drift_downwind() {
+    use crate::config::Config;
+    use crate::world::World;
+
+    let mut world = World::builder()
+        .config(Config {
+            starting_ants: 0,
+            cluster_spawn_chance: 0.0,
+            wind: Wind {
+                speed: 1.0,
+                gust_period: 0,
+                ..Wind::calm()
+            },
+            ..Config::default()
+        })
+        .build();
+    let start = Coordinate::new(100.0, 100.0);
+    let scent = world.config.food_pheromone.lay(&start, 0.0);
+    world.colonies[0].food_scents.sugar.push(scent);
+    for _ in 0..10 {
+        world.step();
+    }
+
+    let moved = &world.colonies[0].food_scents.sugar[0].position;
+    assert!((moved.x - 110.0).abs() < 0.01);
+    assert!((moved.y - 100.0).abs() < 0.01);
+    // the nest keeps its scent
+    let c = &world.colonies[0];
+    assert!(c.home_scents.iter().any(|s| s.position == c.home_position));
+}
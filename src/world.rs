@@ -0,0 +1,1094 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rand::prelude::*;
+use tracing::{debug, error, info};
+
+use crate::ant::{Ant, ANT_DETECTION_RANGE};
+use crate::brain::{self, Action, AntBrain, Perception};
+use crate::brood::{Brood, Stage, LARVA_FEED_INTERVAL};
+use crate::colony::{self, Colony};
+use crate::combat::{self, Caste, Sighting, FIGHT_RANGE};
+use crate::config::Config;
+use crate::coordinate::Coordinate;
+use crate::corpse::{self, Corpse, NECROMONE_INTERVAL};
+use crate::events::{Event, EventBus};
+use crate::food::{self, FoodKind, Patch, PerFood};
+use crate::hex::HexGrid;
+use crate::invariants::{self, Violation};
+use crate::metrics::Metrics;
+use crate::pheromone::PheromoneKind;
+use crate::profiler::{Phase, Profiler};
+use crate::rng;
+use crate::scent::Scent;
+use crate::terrain::{Heightmap, Terrain};
+use crate::wall::{self, Wall};
+use crate::water::{self, Pond};
+
+pub const ANT_SPEED: f32 = 3.0;
+pub const HOME_SIZE: f32 = 15.0;
+
+/// Ticks between checking whether food patches have been eaten
+pub const PATCH_CHECK_INTERVAL: u64 = 50;
+
+pub const X_SIZE: f32 = 500.0;
+pub const Y_SIZE: f32 = 500.0;
+
+pub trait Bounded {
+    fn enforce_x_bounds(&self) -> f32;
+    fn enforce_y_bounds(&self) -> f32;
+}
+
+impl Bounded for f32 {
+    fn enforce_x_bounds(&self) -> f32 {
+        if *self > X_SIZE {
+            return X_SIZE;
+        } else if *self < 0.0 {
+            return 0.0;
+        } else {
+            return *self;
+        }
+    }
+
+    fn enforce_y_bounds(&self) -> f32 {
+        if *self > Y_SIZE {
+            return Y_SIZE;
+        } else if *self < 0.0 {
+            return 0.0;
+        } else {
+            return *self;
+        }
+    }
+}
+
+pub fn gen_food_cluster(size: u32, x: f32, y: f32) -> Vec<Coordinate> {
+    let mut coords = vec![];
+
+    let x = x.enforce_x_bounds();
+    let y = y.enforce_y_bounds();
+
+    rng::with(|r| {
+        for _ in 0..size {
+            // get baseline variance
+            // TODO make this circular and not squareish or smtn
+            let x_var: f32 = r.gen::<f32>() * (size as f32) / 4.0;
+            let y_var: f32 = r.gen::<f32>() * (size as f32) / 4.0;
+
+            // differ by some amount
+            let x_pos = x_var - ((size as f32) / 2.0) + x;
+            let y_pos = y_var - ((size as f32) / 2.0) + y;
+            coords.push(Coordinate::new(
+                x_pos.enforce_x_bounds(),
+                y_pos.enforce_y_bounds(),
+            ));
+        }
+    });
+
+    return coords;
+}
+
+/// Find the closest food which the ant can reach in its next "hop"
+pub fn reachable_food(pos: &Coordinate, food_positions: &Vec<Coordinate>) -> Option<usize> {
+    let mut food_to_eat: Option<usize> = None;
+    for (i, f) in food_positions.iter().enumerate() {
+        if pos.dist(f) < ANT_SPEED {
+            // ant can reach food in next "hop"
+            food_to_eat = match food_to_eat {
+                None => Some(i),
+                Some(prev_food) => {
+                    // SAFE: the match guarantees f is a valid coordinate
+                    if pos.dist(food_positions.get(prev_food).unwrap()) > pos.dist(f) {
+                        Some(i)
+                    } else {
+                        Some(prev_food)
+                    }
+                }
+            };
+        }
+    }
+    food_to_eat
+}
+
+/// Pick which kind of food an ant should go looking for, favouring whatever the colony is short of
+pub fn choose_forage(stores: &PerFood<u32>, config: &Config) -> FoodKind {
+    let brood_cost = config.brood_cost();
+    // how badly each kind is needed, shrinks as the store fills past what raising an ant costs
+    let need = PerFood::new(|k| brood_cost[k] as f32 / (brood_cost[k] + stores[k] + 1) as f32);
+    let total: f32 = need.iter().map(|(_, n)| n).sum();
+    if total <= 0.0 {
+        return FoodKind::Sugar;
+    }
+    let mut pick = rng::with(|r| r.gen::<f32>()) * total;
+    for (kind, n) in need.iter() {
+        if pick < *n {
+            return kind;
+        }
+        pick -= n;
+    }
+    FoodKind::Protein
+}
+
+/// Builds a world, for when the defaults from `World::new` aren't wanted
+pub struct WorldBuilder {
+    config: Config,
+    seed: Option<u64>,
+    brain: Option<Rc<dyn AntBrain>>,
+    heightmap: Option<Heightmap>,
+    populate: bool,
+}
+
+impl WorldBuilder {
+    pub fn new() -> WorldBuilder {
+        WorldBuilder {
+            config: Config::default(),
+            seed: None,
+            brain: None,
+            heightmap: None,
+            populate: true,
+        }
+    }
+
+    pub fn config(mut self, config: Config) -> WorldBuilder {
+        self.config = config;
+        self
+    }
+
+    /// Seed the random number generator before the world is generated, making the run repeatable
+    pub fn seed(mut self, seed: u64) -> WorldBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The brain to give every ant the colonies spawn
+    pub fn brain(mut self, brain: Rc<dyn AntBrain>) -> WorldBuilder {
+        self.brain = Some(brain);
+        self
+    }
+
+    /// The lie of the land, in place of any hills the config asks for
+    pub fn heightmap(mut self, heightmap: Heightmap) -> WorldBuilder {
+        self.heightmap = Some(heightmap);
+        self
+    }
+
+    /// Skip laying down the home scent and food, leaving the world to be filled in by hand
+    pub fn empty(mut self) -> WorldBuilder {
+        self.populate = false;
+        self
+    }
+
+    /// Panics if the config isn't valid, see `Config::validate`
+    pub fn build(self) -> World {
+        if let Err(e) = self.config.validate() {
+            panic!("invalid config: {}", e);
+        }
+        if let Some(seed) = self.seed {
+            rng::seed(seed);
+        }
+        let mut world = World::with_config(self.config);
+        if let Some(map) = self.heightmap {
+            world.terrain = Some(Terrain {
+                map,
+                elevation: world.config.elevation,
+            });
+        }
+        if let Some(brain) = self.brain {
+            for c in world.colonies.iter_mut() {
+                c.brain = brain.clone();
+            }
+        }
+        if self.populate {
+            world.populate();
+        }
+        debug!(seed = ?self.seed, config = ?world.config, "world built");
+        world
+    }
+}
+
+impl Default for WorldBuilder {
+    fn default() -> WorldBuilder {
+        WorldBuilder::new()
+    }
+}
+
+/// The simulation itself, free of any rendering concerns
+pub struct World {
+    pub config: Config,
+    pub metrics: Metrics,
+    pub events: EventBus,
+    pub tick: u64,
+    pub colonies: Vec<Colony>,
+    pub food_positions: PerFood<Vec<Coordinate>>,
+    pub ants: Vec<Ant>,
+    /// the clusters of food the world started with, so they can grow back
+    pub patches: Vec<Patch>,
+    /// bodies lying on the ground, carried ones belong to the ant carrying them
+    pub corpses: Vec<Corpse>,
+    pub necromone: Vec<Scent>,
+    /// hills and hollows, None when the ground is flat
+    pub terrain: Option<Terrain>,
+    pub water: Vec<Pond>,
+    pub walls: Vec<Wall>,
+    /// times each phase of a tick when enabled
+    pub profiler: Profiler,
+    /// the first thing the invariant checks found wrong, the world stops advancing once set
+    pub violation: Option<Violation>,
+    pub(crate) next_ant_id: u64,
+    /// food balance the invariant checks hold the world to, see `invariants::food_ledger`
+    pub(crate) food_ledger: Option<i64>,
+}
+
+impl World {
+    /// An empty world with the default config, see `World::builder` for a ready to run one
+    pub fn new() -> World {
+        World::with_config(Config::default())
+    }
+
+    pub fn builder() -> WorldBuilder {
+        WorldBuilder::new()
+    }
+
+    pub fn with_config(config: Config) -> World {
+        // the queens are the first ants
+        let mut next_ant_id = 0;
+        let colonies = colony::nest_sites(config.colonies, config.colony_spacing)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (home, facing))| {
+                next_ant_id += 1;
+                Colony::new(i, home, facing, next_ant_id - 1, &config)
+            })
+            .collect();
+        let terrain = if config.hills > 0 {
+            Some(Terrain {
+                map: Heightmap::generate(config.hills),
+                elevation: config.elevation,
+            })
+        } else {
+            None
+        };
+        World {
+            config,
+            metrics: Metrics::default(),
+            events: EventBus::new(),
+            tick: 0,
+            colonies,
+            food_positions: PerFood::default(),
+            ants: vec![],
+            patches: vec![],
+            corpses: vec![],
+            necromone: vec![],
+            terrain,
+            water: vec![],
+            walls: vec![],
+            profiler: Profiler::new(),
+            next_ant_id,
+            food_ledger: None,
+            violation: None,
+        }
+    }
+
+    /// Lay down the permanent home scents, fill the ponds, scatter the initial food clusters and
+    /// hatch the starting workers
+    pub fn populate(&mut self) {
+        for c in 0..self.colonies.len() {
+            for _ in 0..self.config.starting_ants {
+                self.spawn_ant(c);
+            }
+        }
+
+        for c in self.colonies.iter_mut() {
+            c.home_scents.push(Scent {
+                position: c.home_position.clone(),
+                direction: 0.0,
+                life: u32::MAX,
+            });
+        }
+
+        let nests = self
+            .colonies
+            .iter()
+            .map(|c| c.home_position.clone())
+            .collect();
+        self.water = water::scatter(self.config.ponds, self.config.pond_radius, &nests);
+
+        // gen food clusters
+        for i in 0..self.config.food_clusters {
+            let kind = if i < self.config.protein_clusters {
+                FoodKind::Protein
+            } else {
+                FoodKind::Sugar
+            };
+            // get baseline variance
+            let (x, y) = rng::with(|r| (r.gen::<f32>() * X_SIZE, r.gen::<f32>() * Y_SIZE));
+
+            self.place_cluster(kind, x, y, self.config.food_cluster_size);
+        }
+        info!(
+            colonies = self.colonies.len(),
+            ants = self.ants.len(),
+            sugar = self.food_positions.sugar.len(),
+            protein = self.food_positions.protein.len(),
+            ponds = self.water.len(),
+            hills = self.terrain.is_some(),
+            "world populated"
+        );
+    }
+
+    /// Put down a new patch of food which will grow back once eaten
+    pub fn place_cluster(&mut self, kind: FoodKind, x: f32, y: f32, size: u32) {
+        let mut patch = Patch::new(kind, x.enforce_x_bounds(), y.enforce_y_bounds());
+        let mut cluster = patch.grow(size);
+        self.food_positions[kind].append(&mut cluster);
+        self.patches.push(patch);
+    }
+
+    /// Put down a cluster of food which won't grow back once eaten
+    pub fn place_food(&mut self, kind: FoodKind, x: f32, y: f32, size: u32) {
+        let mut cluster = gen_food_cluster(size, x.enforce_x_bounds(), y.enforce_y_bounds());
+        self.food_positions[kind].append(&mut cluster);
+    }
+
+    /// Scatter new clusters at random and regrow patches which have been eaten, more food
+    /// appears in the good seasons, only the patches grow back
+    pub fn grow_food(&mut self) {
+        let abundance = food::abundance(&self.config, self.tick);
+        let size = (self.config.food_cluster_size as f32 * abundance).round() as u32;
+
+        if self.config.cluster_spawn_chance > 0.0 {
+            let (roll, x, y, kind_roll) = rng::with(|r| {
+                (
+                    r.gen::<f32>(),
+                    r.gen::<f32>(),
+                    r.gen::<f32>(),
+                    r.gen::<f32>(),
+                )
+            });
+            if roll < self.config.cluster_spawn_chance * abundance {
+                // keep the same mix of food as the world started with
+                let protein =
+                    self.config.protein_clusters as f32 / self.config.food_clusters.max(1) as f32;
+                let kind = if kind_roll < protein {
+                    FoodKind::Protein
+                } else {
+                    FoodKind::Sugar
+                };
+                self.place_food(kind, x * X_SIZE, y * Y_SIZE, size);
+                self.metrics.food_grown += size as u64;
+                debug!(tick = self.tick, ?kind, size, "food appeared");
+            }
+        }
+
+        if !self.tick.is_multiple_of(PATCH_CHECK_INTERVAL) {
+            return;
+        }
+        for patch in self.patches.iter_mut() {
+            let food = &mut self.food_positions[patch.kind];
+            if food.iter().any(|f| f.dist(&patch.centre) < patch.radius) {
+                patch.depleted_at = None;
+                continue;
+            }
+            match patch.depleted_at {
+                None => patch.depleted_at = Some(self.tick),
+                Some(t)
+                    if self.config.regrowth_delay > 0
+                        && self.tick - t >= self.config.regrowth_delay as u64 =>
+                {
+                    let mut cluster = patch.grow(size);
+                    self.metrics.food_grown += cluster.len() as u64;
+                    debug!(tick = self.tick, kind = ?patch.kind, size, "patch grew back");
+                    food.append(&mut cluster);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    pub fn ant(&self, id: u64) -> Option<&Ant> {
+        self.ants.iter().find(|a| a.id == id)
+    }
+
+    /// Every ant within `range` of `pos`
+    pub fn ants_near(&self, pos: &Coordinate, range: f32) -> Vec<&Ant> {
+        self.ants
+            .iter()
+            .filter(|a| a.position.dist(pos) < range)
+            .collect()
+    }
+
+    /// Every piece of food of the given kind within `range` of `pos`
+    pub fn food_near(&self, kind: FoodKind, pos: &Coordinate, range: f32) -> Vec<&Coordinate> {
+        self.food_positions[kind]
+            .iter()
+            .filter(|f| f.dist(pos) < range)
+            .collect()
+    }
+
+    /// The scents of the given kind laid by a colony, `food` picks which food trail is wanted
+    pub fn scents(&self, colony: usize, kind: PheromoneKind, food: FoodKind) -> &Vec<Scent> {
+        self.colonies[colony].scents(kind, food)
+    }
+
+    /// The grid ants move about on, None when they roam freely
+    pub fn grid(&self) -> Option<HexGrid> {
+        if self.config.hex_size > 0.0 {
+            Some(HexGrid {
+                size: self.config.hex_size,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// True once there are no ants or brood left and no queen able to lay more
+    pub fn collapsed(&self) -> bool {
+        self.ants.is_empty()
+            && self
+                .colonies
+                .iter()
+                .all(|c| c.brood.is_empty() && !c.can_lay(&self.config))
+    }
+
+    /// Let the queens lay, feed the larvae and bring on the brood, pupae which are ready
+    /// emerge as new ants
+    pub fn tend_brood(&mut self) {
+        for i in 0..self.colonies.len() {
+            let colony = &mut self.colonies[i];
+            if let Some(q) = colony.queen.as_mut() {
+                q.life = q.life.saturating_sub(1);
+                q.since_laid += 1;
+                if q.life > 0
+                    && q.since_laid >= self.config.egg_interval
+                    && colony.stores.sugar >= self.config.egg_sugar
+                {
+                    q.since_laid = 0;
+                    colony.stores.sugar -= self.config.egg_sugar;
+                    self.metrics.food_eaten += self.config.egg_sugar as u64;
+                    colony.brood.push(Brood::new());
+                    self.metrics.eggs_laid += 1;
+                }
+            }
+            if colony.queen.as_ref().is_some_and(|q| q.life == 0) {
+                // SAFE: we have just checked she is there
+                let q = colony.queen.take().unwrap();
+                self.metrics.ants_died += 1;
+                self.events.emit(
+                    self.tick,
+                    Event::QueenDied {
+                        ant: q.id,
+                        position: q.position.clone(),
+                    },
+                );
+                self.corpses.push(Corpse::new(q.position));
+            }
+
+            let config = &self.config;
+            let protein = &mut colony.stores.protein;
+            let mut emerged = 0;
+            let mut fed = 0;
+            let before = colony.brood.len();
+            colony.brood.retain_mut(|b| {
+                b.age += 1;
+                match b.stage {
+                    Stage::Egg => {
+                        if b.age >= config.egg_time {
+                            b.advance(Stage::Larva);
+                        }
+                        true
+                    }
+                    Stage::Larva => {
+                        if b.fed < config.larva_protein
+                            && b.age % LARVA_FEED_INTERVAL == 0
+                            && *protein > 0
+                        {
+                            *protein -= 1;
+                            b.fed += 1;
+                            fed += 1;
+                        }
+                        if b.fed >= config.larva_protein && b.age >= config.larva_time {
+                            b.advance(Stage::Pupa);
+                            true
+                        } else {
+                            // a larva left hungry for too long starves
+                            b.age < config.larva_time * 2
+                        }
+                    }
+                    Stage::Pupa => {
+                        if b.age >= config.pupa_time {
+                            emerged += 1;
+                            false
+                        } else {
+                            true
+                        }
+                    }
+                }
+            });
+            self.metrics.food_eaten += fed;
+            self.metrics.brood_died += (before - colony.brood.len() - emerged) as u64;
+            for _ in 0..emerged {
+                self.spawn_ant(i);
+            }
+        }
+    }
+
+    /// Spawn a new ant at a colony's home using the colony's brain
+    pub fn spawn_ant(&mut self, colony: usize) {
+        let c = &self.colonies[colony];
+        let mut ant = Ant::new(
+            self.next_ant_id,
+            c.home_position.x,
+            c.home_position.y,
+            c.brain.clone(),
+        );
+        ant.colony = colony;
+        if let Some(grid) = self.grid() {
+            ant.position = grid.snap(&ant.position);
+            ant.previous_position = ant.position.clone();
+        }
+        ant.life = self.config.ant_life;
+        ant.food_kind = choose_forage(&c.stores, &self.config);
+        if rng::with(|r| r.gen::<f32>()) < self.config.soldier_fraction {
+            ant.caste = Caste::Soldier;
+        }
+        ant.health = ant.caste.max_health();
+        self.next_ant_id += 1;
+        self.metrics.ants_spawned += 1;
+        self.events.emit(
+            self.tick,
+            Event::AntSpawned {
+                ant: ant.id,
+                position: ant.position.clone(),
+            },
+        );
+        self.ants.push(ant);
+    }
+
+    /// Age every scent by a tick, letting them spread, blow about and run downhill and removing
+    /// those which have faded away
+    pub fn decay_scents(&mut self) {
+        let wind = &self.config.wind;
+        let tick = self.tick;
+        let terrain = self.terrain.as_ref();
+        let pooling = self.config.scent_pooling;
+        for c in self.colonies.iter_mut() {
+            let home = c.home_position.clone();
+            for (kind, scents) in c.trails_mut() {
+                let pheromone = self.config.pheromone(kind);
+                scents.retain_mut(|s| {
+                    // the scent marking the nest itself stays put
+                    if kind != PheromoneKind::Home || s.position != home {
+                        wind.carry(s, tick);
+                        if let Some(t) = terrain {
+                            t.pool(s, pooling);
+                        }
+                    }
+                    pheromone.age(s)
+                });
+            }
+        }
+        self.necromone.retain_mut(|s| {
+            wind.carry(s, tick);
+            if let Some(t) = terrain {
+                t.pool(s, pooling);
+            }
+            s.life -= 1;
+            s.life > 0
+        });
+    }
+
+    fn middens(&self) -> Vec<Coordinate> {
+        self.colonies
+            .iter()
+            .map(|c| c.midden_position.clone())
+            .collect()
+    }
+
+    /// Age the corpses lying around, those outside a midden give off necromone and, if the
+    /// config allows, old ones rot down into protein
+    pub fn tend_corpses(&mut self) {
+        let decay = self.config.corpse_decay;
+        let middens = self.middens();
+        for c in self.corpses.iter_mut() {
+            c.age += 1;
+            if c.age % NECROMONE_INTERVAL == 0 && !corpse::in_any_midden(&c.position, &middens) {
+                self.necromone.push(c.emit_necromone());
+            }
+        }
+        if decay == 0 {
+            return;
+        }
+        let rotted: Vec<Corpse> = self.corpses.extract_if(.., |c| c.age >= decay).collect();
+        for c in rotted.iter() {
+            let mut new_food =
+                gen_food_cluster(self.config.corpse_food, c.position.x, c.position.y);
+            self.metrics.corpses_decayed += 1;
+            self.food_positions[FoodKind::Protein].append(&mut new_food);
+        }
+    }
+
+    /// Advance the simulation by one tick, with every ant following its brain
+    pub fn step(&mut self) {
+        self.step_with(&HashMap::new());
+    }
+
+    /// Advance the simulation by one tick
+    ///
+    /// # Arguments
+    /// - `overrides` actions to take in place of the brain, keyed by ant id
+    pub fn step_with(&mut self, overrides: &HashMap<u64, Vec<Action>>) {
+        if self.violation.is_some() {
+            return;
+        }
+        if self.config.check_invariants && self.food_ledger.is_none() {
+            self.food_ledger = Some(invariants::food_ledger(self));
+        }
+        self.tick += 1;
+        let started = self.profiler.start();
+        self.tend_brood();
+        self.profiler.stop(Phase::Spawn, started);
+        let started = self.profiler.start();
+        self.decay_scents();
+        self.profiler.stop(Phase::ScentDecay, started);
+        let started = self.profiler.start();
+        self.grow_food();
+        self.profiler.stop(Phase::FoodGrowth, started);
+        let started = self.profiler.start();
+        self.tend_corpses();
+        self.profiler.stop(Phase::Cleanup, started);
+        let middens = self.middens();
+        let grid = self.grid();
+        // ants sense and act one after another, so these phases are timed ant by ant
+        let ants_started = self.profiler.start();
+        let sensing = self.profiler.scope(Phase::Sensing);
+        // enemies are only about when there is more than one colony
+        let sightings: Vec<Sighting> = if self.colonies.len() > 1 {
+            self.ants.iter().map(Sighting::of).collect()
+        } else {
+            vec![]
+        };
+        let mut bites: Vec<(u64, f32)> = vec![];
+        // who is where, so ants at the water's edge know if they have help
+        let (crowd, bridges): (Vec<(usize, Coordinate)>, Vec<Coordinate>) = if self.water.is_empty()
+        {
+            (vec![], vec![])
+        } else {
+            (
+                self.ants
+                    .iter()
+                    .map(|a| (a.colony, a.position.clone()))
+                    .collect(),
+                self.ants
+                    .iter()
+                    .filter(|a| a.bridging > 0)
+                    .map(|a| a.position.clone())
+                    .collect(),
+            )
+        };
+        drop(sensing);
+        for a in self.ants.iter_mut() {
+            a.begin_tick();
+            a.life = a.life.saturating_sub(1);
+            // if the ant is dead, leave its body behind, along with anything it was carrying
+            if a.life == 0 {
+                self.metrics.ants_died += 1;
+                self.events.emit(
+                    self.tick,
+                    Event::AntDied {
+                        ant: a.id,
+                        position: a.position.clone(),
+                    },
+                );
+                leave_body(
+                    a,
+                    &mut self.corpses,
+                    &mut self.food_positions,
+                    &mut self.events,
+                    self.tick,
+                );
+                continue;
+            }
+            // ants holding a bridge stay put until their time is up
+            if a.bridging > 0 {
+                a.bridging -= 1;
+                continue;
+            }
+            let colony = &mut self.colonies[a.colony];
+            let sensing = self.profiler.scope(Phase::Sensing);
+            let enemy = combat::nearest_enemy(&sightings, a, ANT_DETECTION_RANGE);
+            // else let the brain decide what to do
+            let actions = match overrides.get(&a.id) {
+                Some(actions) => actions.clone(),
+                None => {
+                    let perception = Perception {
+                        position: a.position.clone(),
+                        nose: self.config.wind.nose(&a.position, self.tick),
+                        direction: a.direction,
+                        speed: a.speed,
+                        has_food: a.has_food,
+                        food_kind: a.food_kind,
+                        caste: a.caste,
+                        at_home: colony.at_home(&a.position),
+                        reachable_food: reachable_food(
+                            &a.position,
+                            &self.food_positions[a.food_kind],
+                        ),
+                        carrying_corpse: a.corpse.is_some(),
+                        at_midden: corpse::in_midden(&a.position, &colony.midden_position),
+                        reachable_corpse: corpse::reachable_corpse(
+                            &a.position,
+                            &self.corpses,
+                            &middens,
+                        )
+                        .is_some(),
+                        enemy: enemy.map(|e| e.position.clone()),
+                        enemy_in_reach: enemy
+                            .is_some_and(|e| e.position.dist(&a.position) < FIGHT_RANGE),
+                        home: &colony.home_position,
+                        midden: &colony.midden_position,
+                        food: &self.food_positions[a.food_kind],
+                        home_scents: &colony.home_scents,
+                        food_scents: &colony.food_scents[a.food_kind],
+                        alarm_scents: &colony.alarm_scents,
+                        repellent_scents: &colony.repellent_scents,
+                        exploration_scents: &colony.exploration_scents,
+                        necromone: &self.necromone,
+                        config: &self.config,
+                    };
+                    a.brain.decide(&perception)
+                }
+            };
+            drop(sensing);
+            let mut drowned = false;
+            for action in actions {
+                let phase = match action {
+                    Action::PickUp | Action::Drop => Phase::FoodPickup,
+                    _ => Phase::Movement,
+                };
+                let _timed = self.profiler.scope(phase);
+                match action {
+                    Action::Turn(d) => a.direction = d,
+                    Action::Move => {
+                        let from = a.position.clone();
+                        let speed = match self.terrain.as_ref() {
+                            Some(t) => t.speed(&a.position, a.direction, a.speed),
+                            None => a.speed,
+                        };
+                        match grid.as_ref() {
+                            Some(grid) => a.hop(grid, speed),
+                            None => a.stride(speed),
+                        }
+                        if wall::blocked(&self.walls, &from, &a.position) {
+                            // bump into the wall and turn back
+                            a.position = from;
+                            a.direction = (a.direction + 180.0) % 360.0;
+                            continue;
+                        }
+                        if let Some(t) = self.terrain.as_ref() {
+                            // climbing wears an ant out, though never to death mid stride
+                            let climb = t.height(&a.position) - t.height(&from);
+                            if climb > 0.0 {
+                                a.fatigue += climb * self.config.climb_cost;
+                                let owed = a.fatigue.floor();
+                                a.fatigue -= owed;
+                                a.life = a.life.saturating_sub(owed as u32).max(1);
+                            }
+                        }
+                        if water::in_water(&a.position, &self.water)
+                            && !water::bridged(&a.position, &bridges)
+                        {
+                            let helpers = crowd
+                                .iter()
+                                .filter(|(c, p)| {
+                                    *c == a.colony && p.dist(&from) < water::SHORE_RANGE
+                                })
+                                .count();
+                            if helpers >= self.config.bridge_ants as usize {
+                                a.bridging = self.config.bridge_time;
+                            } else {
+                                drowned = true;
+                                break;
+                            }
+                        }
+                    }
+                    Action::PickUp if !a.has_food && a.corpse.is_none() => {
+                        let food = &mut self.food_positions[a.food_kind];
+                        if let Some(f) = reachable_food(&a.position, food) {
+                            let f = food.remove(f);
+                            // on a grid we stay in our cell rather than stepping onto the food
+                            if grid.is_none() {
+                                a.position = f;
+                            }
+                            a.has_food = true;
+                            self.metrics.food_collected += 1;
+                            self.events.emit(
+                                self.tick,
+                                Event::FoodPickedUp {
+                                    ant: a.id,
+                                    position: a.position.clone(),
+                                    kind: a.food_kind,
+                                },
+                            );
+                        }
+                    }
+                    Action::PickUp => {}
+                    Action::Drop => {
+                        if a.has_food && colony.at_home(&a.position) {
+                            a.has_food = false;
+                            colony.stores[a.food_kind] += 1;
+                            self.metrics.food_delivered += 1;
+                            self.events.emit(
+                                self.tick,
+                                Event::FoodDelivered {
+                                    ant: a.id,
+                                    position: a.position.clone(),
+                                    kind: a.food_kind,
+                                },
+                            );
+                            // head back out for whatever the colony needs most now
+                            a.food_kind = choose_forage(&colony.stores, &self.config);
+                        }
+                    }
+                    Action::PickUpCorpse if !a.has_food && a.corpse.is_none() => {
+                        if let Some(c) =
+                            corpse::reachable_corpse(&a.position, &self.corpses, &middens)
+                        {
+                            a.corpse = Some(self.corpses.remove(c));
+                            self.events.emit(
+                                self.tick,
+                                Event::CorpsePickedUp {
+                                    ant: a.id,
+                                    position: a.position.clone(),
+                                },
+                            );
+                        }
+                    }
+                    Action::PickUpCorpse => {}
+                    Action::DropCorpse => {
+                        if corpse::in_midden(&a.position, &colony.midden_position) {
+                            if let Some(mut c) = a.corpse.take() {
+                                c.position = a.position.clone();
+                                self.corpses.push(c);
+                                self.metrics.corpses_buried += 1;
+                                self.events.emit(
+                                    self.tick,
+                                    Event::CorpseBuried {
+                                        ant: a.id,
+                                        position: a.position.clone(),
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Action::Attack => {
+                        if let Some(e) = enemy {
+                            if e.position.dist(&a.position) < FIGHT_RANGE {
+                                bites.push((e.ant, a.caste.strength()));
+                                self.events.emit(
+                                    self.tick,
+                                    Event::AntAttacked {
+                                        ant: a.id,
+                                        position: a.position.clone(),
+                                        target: e.ant,
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Action::Deposit(kind) => {
+                        // alarms point the way we are facing, towards the trouble
+                        let direction = match kind {
+                            PheromoneKind::Alarm => a.direction,
+                            _ => brain::reverse(a.direction),
+                        };
+                        let scent = self.config.pheromone(kind).lay(&a.position, direction);
+                        let scents = colony.scents_mut(kind, a.food_kind);
+                        match grid.as_ref() {
+                            Some(grid) => grid.deposit(scents, scent),
+                            None => scents.push(scent),
+                        }
+                        self.events.emit(
+                            self.tick,
+                            Event::ScentDeposited {
+                                ant: a.id,
+                                position: a.position.clone(),
+                                kind,
+                            },
+                        );
+                    }
+                }
+            }
+            // swept away, along with anything being carried
+            if drowned {
+                a.life = 0;
+                self.metrics.ants_drowned += 1;
+                if a.has_food {
+                    self.metrics.food_lost += 1;
+                }
+                self.events.emit(
+                    self.tick,
+                    Event::AntDrowned {
+                        ant: a.id,
+                        position: a.position.clone(),
+                    },
+                );
+                drop_corpse(a, &mut self.corpses, &mut self.events, self.tick);
+                continue;
+            }
+            if a.life > 0 && a.position != a.position.check_bounds(0.0, X_SIZE, 0.0, Y_SIZE) {
+                if a.has_food {
+                    self.metrics.food_lost += 1;
+                }
+                self.events.emit(
+                    self.tick,
+                    Event::AntLost {
+                        ant: a.id,
+                        position: a.position.clone(),
+                    },
+                );
+                drop_corpse(a, &mut self.corpses, &mut self.events, self.tick);
+            }
+        }
+
+        self.profiler.lay_out(
+            ants_started,
+            &[Phase::Sensing, Phase::Movement, Phase::FoodPickup],
+        );
+
+        // fold the scents laid this tick and those blown about back into one per cell
+        if let Some(grid) = grid.as_ref() {
+            let started = self.profiler.start();
+            for c in self.colonies.iter_mut() {
+                let home = c.home_position.clone();
+                for (kind, scents) in c.trails_mut() {
+                    grid.merge(scents, (kind == PheromoneKind::Home).then_some(&home));
+                }
+            }
+            self.profiler.stop(Phase::ScentDecay, started);
+        }
+
+        let started = self.profiler.start();
+        // bites land once everyone has moved, so no ant gets to strike first
+        for (target, damage) in bites {
+            if let Some(a) = self.ants.iter_mut().find(|a| a.id == target) {
+                a.health -= damage;
+            }
+        }
+        for a in self.ants.iter_mut() {
+            if a.life > 0 && a.health <= 0.0 {
+                a.life = 0;
+                self.metrics.ants_killed += 1;
+                self.events.emit(
+                    self.tick,
+                    Event::AntKilled {
+                        ant: a.id,
+                        position: a.position.clone(),
+                    },
+                );
+                leave_body(
+                    a,
+                    &mut self.corpses,
+                    &mut self.food_positions,
+                    &mut self.events,
+                    self.tick,
+                );
+            }
+        }
+
+        self.ants.retain(|a| {
+            a.life > 0 && a.position == a.position.check_bounds(0.0, X_SIZE, 0.0, Y_SIZE)
+        });
+        self.profiler.stop(Phase::Cleanup, started);
+        self.profiler.finish();
+
+        if let Some(ledger) = self.food_ledger.filter(|_| self.config.check_invariants) {
+            if let Err(v) = invariants::check(self, ledger) {
+                error!(tick = v.tick, entity = %v.entity, problem = %v.problem, "{}", v.context);
+                self.violation = Some(v);
+            }
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> World {
+        World::new()
+    }
+}
+
+/// Leave a dead ant's body where it fell, dropping anything it was carrying
+fn leave_body(
+    ant: &mut Ant,
+    corpses: &mut Vec<Corpse>,
+    food: &mut PerFood<Vec<Coordinate>>,
+    events: &mut EventBus,
+    tick: u64,
+) {
+    corpses.push(Corpse::new(ant.position.clone()));
+    drop_corpse(ant, corpses, events, tick);
+    if ant.has_food {
+        ant.has_food = false;
+        food[ant.food_kind].push(ant.position.clone());
+    }
+}
+
+/// Put down any corpse the ant is carrying where it is, or on the edge of the map if it has
+/// wandered off
+fn drop_corpse(ant: &mut Ant, corpses: &mut Vec<Corpse>, events: &mut EventBus, tick: u64) {
+    if let Some(mut c) = ant.corpse.take() {
+        c.position = ant.position.check_bounds(0.0, X_SIZE, 0.0, Y_SIZE);
+        events.emit(
+            tick,
+            Event::CorpseDropped {
+                ant: ant.id,
+                position: c.position.clone(),
+            },
+        );
+        corpses.push(c);
+    }
+}
+
+#[test]
+fn foragers_go_after_what_the_colony_lacks() {
+    rng::seed(1);
+    let config = Config::default();
+    let stores = PerFood {
+        sugar: 100,
+        protein: 0,
+    };
+    let protein = (0..100)
+        .filter(|_| choose_forage(&stores, &config) == FoodKind::Protein)
+        .count();
+    assert!(protein > 90);
+}
+
+#[test]
+fn eaten_patches_grow_back() {
+    let mut world = World::builder()
+        .seed(2)
+        .config(Config {
+            food_clusters: 1,
+            protein_clusters: 0,
+            starting_sugar: 0,
+            starting_ants: 0,
+            cluster_spawn_chance: 0.0,
+            regrowth_delay: 100,
+            season_length: 0,
+            ..Config::default()
+        })
+        .build();
+    world.food_positions.sugar.clear();
+    for _ in 0..200 {
+        world.step();
+    }
+
+    assert_eq!(world.food_positions.sugar.len(), 150);
+    assert_eq!(world.metrics.food_grown, 150);
+
+    // food which turns up at random comes and goes without adding to the patches
+    world.config.cluster_spawn_chance = 0.5;
+    for _ in 0..2000 {
+        world.step();
+    }
+    assert!(world.metrics.food_grown > 150 * 100);
+    assert_eq!(world.patches.len(), 1);
+}
@@ -0,0 +1,36 @@
+"""Smoke tests for the python bindings, run with `make pytest`"""
+
+import numpy as np
+import pytest
+
+import ants
+
+
+def test_world_steps_and_reports():
+    world = ants.World(seed=3)
+    world.step(20)
+    assert world.tick == 20
+
+    a = world.ants()
+    n = len(a["id"])
+    assert n > 0
+    assert a["position"].shape == (n, 2)
+    for key in ["direction", "has_food", "life", "colony", "caste", "health", "bridging", "food_kind"]:
+        assert a[key].shape == (n,), key
+
+    m = world.metrics()
+    assert m["tick"] == 20
+    assert m["ants"] == n
+    assert m["colonies"] == 1
+
+    # the same seed plays out the same way
+    again = ants.World(seed=3)
+    again.step(20)
+    assert np.array_equal(again.ants()["position"], a["position"])
+
+
+def test_invalid_config_raises():
+    config = ants.Config()
+    config.ant_life = 0
+    with pytest.raises(ValueError):
+        ants.World(config)